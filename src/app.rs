@@ -1,13 +1,86 @@
-use crate::infrastructure::{IncomingMessage, SignalClient};
+use crate::infrastructure::{Contact, Group, Identity, IncomingMessage, SignalClient, UserStatus};
+use crate::util::{
+    DEFAULT_PREVIEW_LENGTH, TimestampFormat, format_duration, now_millis, normalize_e164, parse_date, parse_duration, relative_unit, truncate_to_width,
+};
 use crate::storage::{
-    Conversation, ConversationType, Message, MessageContent, SqliteStorage, StorageRepository,
+    AttachmentInfo, Conversation, ConversationType, DeliveryState, DeliveryStatus, GroupMember, LinkPreview, Message,
+    MessageContent, Quote, Reaction, SqliteStorage, StorageRepository,
 };
 use ratatui::layout::Rect;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListDensity {
+    Compact,
+    #[default]
+    Comfortable,
+}
+
+impl ListDensity {
+    pub fn item_height(self) -> u16 {
+        match self {
+            ListDensity::Compact => 2,
+            ListDensity::Comfortable => 4,
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            ListDensity::Compact => ListDensity::Comfortable,
+            ListDensity::Comfortable => ListDensity::Compact,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListDensity::Compact => "compact",
+            ListDensity::Comfortable => "comfortable",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "compact" => Some(ListDensity::Compact),
+            "comfortable" => Some(ListDensity::Comfortable),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationPrivacy {
+    #[default]
+    Full,
+    SenderOnly,
+    Generic,
+}
+
+impl NotificationPrivacy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(NotificationPrivacy::Full),
+            "sender" => Some(NotificationPrivacy::SenderOnly),
+            "generic" => Some(NotificationPrivacy::Generic),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationPrivacy::Full => "full",
+            NotificationPrivacy::SenderOnly => "sender",
+            NotificationPrivacy::Generic => "generic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Focus {
     Conversations,
     ConversationFilter,
@@ -16,12 +89,27 @@ pub enum Focus {
     FileBrowser,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionHealth {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum SendTarget {
     Direct(String),
     Group(String),
 }
 
+impl SendTarget {
+    pub fn recipient(&self) -> &str {
+        match self {
+            SendTarget::Direct(recipient) | SendTarget::Group(recipient) => recipient,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct InputState {
     pub text: String,
@@ -34,6 +122,11 @@ impl InputState {
         self.cursor += c.len_utf8();
     }
 
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
     pub fn delete_back(&mut self) {
         if self.cursor > 0 {
             let prev = self.text[..self.cursor]
@@ -94,13 +187,25 @@ pub struct DirEntry {
     pub size: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileBrowserPurpose {
+    #[default]
+    Attach,
+    ExportAttachments,
+}
+
 #[derive(Debug)]
 pub struct FileBrowserState {
+    pub purpose: FileBrowserPurpose,
     pub current_dir: PathBuf,
     pub entries: Vec<DirEntry>,
     pub selected: usize,
     pub marked: HashSet<usize>,
+    pub range_anchor: Option<(usize, HashSet<usize>)>,
     pub show_hidden: bool,
+    pub bookmarks: Vec<PathBuf>,
+    pub bookmarks_file: Option<PathBuf>,
+    pub showing_bookmarks: bool,
 }
 
 impl Default for FileBrowserState {
@@ -109,11 +214,16 @@ impl Default for FileBrowserState {
             .map(PathBuf::from)
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")));
         let mut state = Self {
+            purpose: FileBrowserPurpose::default(),
             current_dir,
             entries: Vec::new(),
             selected: 0,
             marked: HashSet::new(),
+            range_anchor: None,
             show_hidden: false,
+            bookmarks: Vec::new(),
+            bookmarks_file: None,
+            showing_bookmarks: false,
         };
         state.refresh();
         state
@@ -125,6 +235,8 @@ impl FileBrowserState {
         self.entries.clear();
         self.selected = 0;
         self.marked.clear();
+        self.range_anchor = None;
+        self.showing_bookmarks = false;
 
         let Ok(read_dir) = std::fs::read_dir(&self.current_dir) else {
             return;
@@ -167,6 +279,9 @@ impl FileBrowserState {
 
     pub fn enter_selected(&mut self) -> Option<PathBuf> {
         let entry = self.entries.get(self.selected)?;
+        if self.showing_bookmarks && !entry.path.is_dir() {
+            return None;
+        }
         if entry.is_dir {
             self.current_dir = entry.path.clone();
             self.refresh();
@@ -186,6 +301,21 @@ impl FileBrowserState {
         }
     }
 
+    pub fn extend_range(&mut self, delta: i32) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let (anchor, base) = match self.range_anchor.take() {
+            Some(range) => range,
+            None => (self.selected, self.marked.clone()),
+        };
+        self.move_selection(delta);
+        let (lo, hi) = (anchor.min(self.selected), anchor.max(self.selected));
+        self.marked = base.clone();
+        self.marked.extend((lo..=hi).filter(|&idx| !self.entries[idx].is_dir));
+        self.range_anchor = Some((anchor, base));
+    }
+
     pub fn get_marked_or_selected(&self) -> Vec<PathBuf> {
         if self.marked.is_empty() {
             if let Some(entry) = self.entries.get(self.selected) {
@@ -210,6 +340,71 @@ impl FileBrowserState {
         }
     }
 
+    pub fn load_bookmarks(&mut self, file: PathBuf) {
+        self.bookmarks = std::fs::read_to_string(&file)
+            .map(|data| {
+                data.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.bookmarks_file = Some(file);
+    }
+
+    fn save_bookmarks(&self) {
+        let Some(file) = &self.bookmarks_file else {
+            return;
+        };
+        let data: Vec<String> = self
+            .bookmarks
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let _ = std::fs::write(file, data.join("\n"));
+    }
+
+    pub fn toggle_bookmark(&mut self) -> bool {
+        let added = match self.bookmarks.iter().position(|p| *p == self.current_dir) {
+            Some(idx) => {
+                self.bookmarks.remove(idx);
+                false
+            }
+            None => {
+                self.bookmarks.push(self.current_dir.clone());
+                true
+            }
+        };
+        self.save_bookmarks();
+        added
+    }
+
+    pub fn show_bookmarks(&mut self) {
+        if self.showing_bookmarks {
+            self.refresh();
+            return;
+        }
+        self.entries = self
+            .bookmarks
+            .iter()
+            .map(|path| {
+                let mut name = path.to_string_lossy().to_string();
+                if !path.is_dir() {
+                    name.push_str(" (missing)");
+                }
+                DirEntry {
+                    name,
+                    path: path.clone(),
+                    is_dir: true,
+                    size: 0,
+                }
+            })
+            .collect();
+        self.selected = 0;
+        self.marked.clear();
+        self.showing_bookmarks = true;
+    }
+
     pub fn toggle_hidden(&mut self) {
         self.show_hidden = !self.show_hidden;
         self.refresh();
@@ -251,6 +446,14 @@ impl MessageSelection {
 }
 
 pub const SCROLL_LINES: usize = 3;
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+pub const MAX_MISSED_HEARTBEATS: u32 = 3;
+pub const SYNC_STALE_THRESHOLD: Duration = Duration::from_secs(15 * 60);
+pub const FLASH_DURATION: Duration = Duration::from_millis(1500);
+pub const TYPING_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+const AUDIO_PLAYERS: [&str; 3] = ["mpv", "ffplay", "paplay"];
+pub const TYPING_INDICATOR_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_LOADED_CONVERSATIONS: usize = 8;
 
 #[derive(Debug)]
 pub struct ConversationView {
@@ -261,6 +464,14 @@ pub struct ConversationView {
     pub selection: Option<MessageSelection>,
     pub visible_range: Option<(usize, usize)>,
     pub last_message_preview: Option<Message>,
+    pub flash: Option<(String, Instant)>,
+    pub failed_sends: HashSet<String>,
+    pub sending: HashSet<String>,
+    pub reactions: HashMap<String, Vec<Reaction>>,
+    pub deliveries: HashMap<String, Vec<DeliveryStatus>>,
+    pub new_below: usize,
+    pub scroll_compensation: Vec<String>,
+    pub revealed_spoilers: HashSet<String>,
 }
 
 impl ConversationView {
@@ -277,6 +488,14 @@ impl ConversationView {
             selection: None,
             visible_range: None,
             last_message_preview,
+            flash: None,
+            failed_sends: HashSet::new(),
+            sending: HashSet::new(),
+            reactions: HashMap::new(),
+            deliveries: HashMap::new(),
+            new_below: 0,
+            scroll_compensation: Vec::new(),
+            revealed_spoilers: HashSet::new(),
         }
     }
 
@@ -285,6 +504,7 @@ impl ConversationView {
             && let Ok(msgs) = storage.list_messages(&self.conversation.id, 100, None)
         {
             self.has_more_messages = msgs.len() >= 100;
+            self.load_message_metadata(&msgs, storage);
             self.messages = Some(msgs);
             self.scroll_to_bottom();
             return true;
@@ -292,27 +512,75 @@ impl ConversationView {
         false
     }
 
+    pub fn unload_messages(&mut self) {
+        self.conversation.unread_count = self.unread_count();
+        self.messages = None;
+        self.scroll_offset = 0;
+        self.has_more_messages = true;
+        self.selection = None;
+        self.visible_range = None;
+        self.failed_sends.clear();
+        self.reactions.clear();
+        self.deliveries.clear();
+        self.new_below = 0;
+        self.scroll_compensation.clear();
+        self.revealed_spoilers.clear();
+    }
+
+    fn load_message_metadata(&mut self, msgs: &[Message], storage: &SqliteStorage) {
+        let ids: Vec<String> = msgs.iter().map(|m| m.id.clone()).collect();
+        if let Ok(reactions) = storage.get_reactions_for_messages(&ids) {
+            self.reactions.extend(reactions);
+        }
+        if let Ok(deliveries) = storage.get_delivery_statuses_for_messages(&ids) {
+            for (id, statuses) in deliveries {
+                if statuses.iter().any(|s| s.state == DeliveryState::Failed) {
+                    self.failed_sends.insert(id.clone());
+                }
+                self.deliveries.insert(id, statuses);
+            }
+        }
+    }
+
+    pub fn single_selected_message(&self) -> Option<&Message> {
+        let sel = self.selection.as_ref()?;
+        if sel.anchor != sel.cursor {
+            return None;
+        }
+        self.messages.as_ref()?.get(sel.cursor)
+    }
+
+    pub fn selected_failed_message(&self) -> Option<&Message> {
+        let sel = self.selection.as_ref()?;
+        let msg = self.messages.as_ref()?.get(sel.cursor)?;
+        self.failed_sends.contains(&msg.id).then_some(msg)
+    }
+
     pub fn collect_image_paths(&self) -> Vec<String> {
         let Some(ref msgs) = self.messages else {
             return Vec::new();
         };
 
-        let mut paths = Vec::new();
-        for msg in msgs.iter().rev() {
-            if let MessageContent::Attachment { attachments } = &msg.content {
-                for att in attachments {
-                    if att
+        attachments_of(msgs.iter().rev())
+            .filter(|att| {
+                !att.view_once
+                    && att
                         .content_type
                         .as_ref()
                         .is_some_and(|ct| ct.starts_with("image/"))
-                        && let Some(path) = &att.local_path
-                    {
-                        paths.push(path.clone());
-                    }
-                }
-            }
-        }
-        paths
+            })
+            .filter_map(|att| att.local_path.clone())
+            .collect()
+    }
+
+    pub fn collect_all_attachments(&self, storage: &SqliteStorage) -> Vec<AttachmentInfo> {
+        let msgs = storage
+            .list_messages(&self.conversation.id, u32::MAX, None)
+            .unwrap_or_default();
+        attachments_of(msgs.iter().rev())
+            .filter(|att| !att.view_once)
+            .cloned()
+            .collect()
     }
 
     pub fn load_older_messages(&mut self, storage: &SqliteStorage) -> Vec<String> {
@@ -320,15 +588,15 @@ impl ConversationView {
             return Vec::new();
         }
 
-        let oldest_timestamp = self
+        let oldest = self
             .messages
             .as_ref()
             .and_then(|msgs| msgs.first())
-            .map(|m| m.timestamp);
+            .map(|m| m.cursor());
 
-        if let Some(before_ts) = oldest_timestamp
+        if let Some(cursor) = oldest
             && let Ok(older_msgs) =
-                storage.list_messages(&self.conversation.id, 100, Some(before_ts))
+                storage.list_messages(&self.conversation.id, 100, Some(&cursor))
         {
             if older_msgs.is_empty() {
                 self.has_more_messages = false;
@@ -336,16 +604,18 @@ impl ConversationView {
             }
 
             self.has_more_messages = older_msgs.len() >= 100;
+            self.load_message_metadata(&older_msgs, storage);
 
             // Collect image paths from newly loaded messages
             let mut paths = Vec::new();
             for msg in &older_msgs {
                 if let MessageContent::Attachment { attachments } = &msg.content {
                     for att in attachments {
-                        if att
-                            .content_type
-                            .as_ref()
-                            .is_some_and(|ct| ct.starts_with("image/"))
+                        if !att.view_once
+                            && att
+                                .content_type
+                                .as_ref()
+                                .is_some_and(|ct| ct.starts_with("image/"))
                             && let Some(path) = &att.local_path
                         {
                             paths.push(path.clone());
@@ -354,21 +624,67 @@ impl ConversationView {
                 }
             }
 
+            let anchors = self.index_anchors();
             if let Some(ref mut msgs) = self.messages {
                 // Prepend older messages
                 let mut new_msgs = older_msgs;
                 new_msgs.append(msgs);
                 *msgs = new_msgs;
 
-                // No scroll_offset adjustment needed - it's line-based from bottom
+                self.restore_index_anchors(anchors);
                 return paths;
             }
         }
         Vec::new()
     }
 
+    fn index_anchors(&self) -> [Option<String>; 4] {
+        let id_at = |idx: usize| {
+            self.messages
+                .as_ref()
+                .and_then(|msgs| msgs.get(idx))
+                .map(|m| m.id.clone())
+        };
+        [
+            self.selection.as_ref().and_then(|s| id_at(s.anchor)),
+            self.selection.as_ref().and_then(|s| id_at(s.cursor)),
+            self.visible_range.and_then(|(start, _)| id_at(start)),
+            self.visible_range.and_then(|(_, end)| id_at(end)),
+        ]
+    }
+
+    fn restore_index_anchors(&mut self, anchors: [Option<String>; 4]) {
+        let Some(ref msgs) = self.messages else {
+            return;
+        };
+        let [anchor, cursor, start, end] =
+            anchors.map(|id| id.and_then(|id| msgs.iter().position(|m| m.id == id)));
+        if let Some(ref mut selection) = self.selection
+            && let (Some(anchor), Some(cursor)) = (anchor, cursor)
+        {
+            selection.anchor = anchor;
+            selection.cursor = cursor;
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            self.visible_range = Some((start, end));
+        }
+    }
+
     pub fn scroll_to_bottom(&mut self) {
         self.scroll_offset = 0;
+        self.new_below = 0;
+        self.scroll_compensation.clear();
+    }
+
+    pub fn unread_count(&self) -> u32 {
+        match self.messages {
+            Some(ref msgs) => msgs.iter().filter(|m| !m.is_read && !m.is_outgoing).count() as u32,
+            None => self.conversation.unread_count,
+        }
+    }
+
+    pub fn has_unread_emphasis(&self) -> bool {
+        !self.conversation.is_muted && self.unread_count() > 0
     }
 
     pub fn unread_incoming_timestamps(&self) -> Vec<i64> {
@@ -381,10 +697,28 @@ impl ConversationView {
     }
 
     pub fn add_message(&mut self, message: Message) {
-        self.last_message_preview = Some(message.clone());
+        if self
+            .last_message_preview
+            .as_ref()
+            .is_none_or(|last| last.sort_key() <= message.sort_key())
+        {
+            self.last_message_preview = Some(message.clone());
+        }
+        if self.messages.is_some() {
+            if self.scroll_offset == 0 || message.is_outgoing {
+                self.scroll_to_bottom();
+            } else {
+                self.new_below += 1;
+                self.scroll_compensation.push(message.id.clone());
+            }
+        }
+        let anchors = self.index_anchors();
         if let Some(ref mut msgs) = self.messages {
-            msgs.push(message);
-            self.scroll_offset = 0;
+            let pos = msgs.partition_point(|m| m.sort_key() <= message.sort_key());
+            msgs.insert(pos, message);
+            self.restore_index_anchors(anchors);
+        } else if !message.is_read && !message.is_outgoing {
+            self.conversation.unread_count += 1;
         }
     }
 
@@ -404,6 +738,79 @@ impl ConversationView {
         }
     }
 
+    pub fn selected_quote(&self) -> Option<&Quote> {
+        let sel = self.selection.as_ref()?;
+        self.messages.as_ref()?.get(sel.cursor)?.quote.as_ref()
+    }
+
+    fn find_message(&self, authors: &[String], mine: bool, timestamp: i64) -> Option<usize> {
+        self.messages.as_ref()?.iter().position(|m| {
+            m.timestamp == timestamp
+                && if m.sender_uuid.is_empty() {
+                    mine && m.is_outgoing
+                } else {
+                    authors.contains(&m.sender_uuid)
+                }
+        })
+    }
+
+    pub fn jump_to_quoted(&mut self, storage: &SqliteStorage, authors: &[String], mine: bool) -> Option<Vec<String>> {
+        let quote = self.selected_quote()?.clone();
+        let mut paths = Vec::new();
+
+        let idx = loop {
+            if let Some(idx) = self.find_message(authors, mine, quote.timestamp) {
+                break idx;
+            }
+            let oldest = self.messages.as_ref()?.first()?.timestamp;
+            if !self.has_more_messages || oldest < quote.timestamp {
+                return None;
+            }
+            let before = self.messages.as_ref().map_or(0, |m| m.len());
+            paths.extend(self.load_older_messages(storage));
+            if self.messages.as_ref().map_or(0, |m| m.len()) == before {
+                return None;
+            }
+        };
+
+        let msgs = self.messages.as_ref()?;
+        self.flash = Some((msgs[idx].id.clone(), Instant::now()));
+        self.selection = Some(MessageSelection {
+            anchor: idx,
+            cursor: idx,
+        });
+        Some(paths)
+    }
+
+    pub fn jump_to_date(&mut self, storage: &SqliteStorage, timestamp: i64) -> Option<Vec<String>> {
+        let target = storage
+            .first_message_on_or_after(&self.conversation.id, timestamp)
+            .ok()??;
+        let mut paths = Vec::new();
+
+        let idx = loop {
+            let msgs = self.messages.as_ref()?;
+            if let Some(idx) = msgs.iter().position(|m| m.id == target.id) {
+                break idx;
+            }
+            let before = msgs.len();
+            if !self.has_more_messages || msgs.first()?.timestamp < target.timestamp {
+                return None;
+            }
+            paths.extend(self.load_older_messages(storage));
+            if self.messages.as_ref().map_or(0, |m| m.len()) == before {
+                return None;
+            }
+        };
+
+        self.flash = Some((target.id, Instant::now()));
+        self.selection = Some(MessageSelection {
+            anchor: idx,
+            cursor: idx,
+        });
+        Some(paths)
+    }
+
     pub fn exit_selection_mode(&mut self) {
         self.selection = None;
     }
@@ -451,7 +858,7 @@ impl ConversationView {
         for idx in range {
             if let Some(msg) = msgs.get(idx) {
                 let text = match &msg.content {
-                    MessageContent::Text { body } => body.clone(),
+                    MessageContent::Text { body } => crate::util::strip_spoilers(body),
                     MessageContent::Attachment { attachments } => {
                         attachments
                             .iter()
@@ -515,6 +922,24 @@ impl ConversationView {
             .collect()
     }
 
+    pub fn send_target(&self) -> Option<SendTarget> {
+        match self.conversation.conversation_type {
+            ConversationType::Direct => {
+                let uuid = self.conversation.recipient_uuid.clone();
+                let recipient = uuid
+                    .clone()
+                    .filter(|u| uuid::Uuid::parse_str(u).is_ok())
+                    .or_else(|| self.conversation.recipient_number.clone())
+                    .or(uuid)?;
+                Some(SendTarget::Direct(recipient))
+            }
+            ConversationType::Group => {
+                let group_id = self.conversation.group_id.clone()?;
+                Some(SendTarget::Group(group_id))
+            }
+        }
+    }
+
     pub fn remote_delete_target(&self) -> Option<RemoteDeleteTarget> {
         match self.conversation.conversation_type {
             ConversationType::Direct => {
@@ -532,6 +957,51 @@ impl ConversationView {
         }
     }
 
+    pub fn open_selected_view_once(
+        &mut self,
+        storage: &SqliteStorage,
+        mut hand_off: impl FnMut(&Path) -> bool,
+    ) -> Vec<(String, PathBuf)> {
+        let Some(ref sel) = self.selection else {
+            return Vec::new();
+        };
+        let Some(ref mut msgs) = self.messages else {
+            return Vec::new();
+        };
+
+        let mut opened = Vec::new();
+        for idx in sel.range() {
+            let Some(msg) = msgs.get_mut(idx) else {
+                continue;
+            };
+            let MessageContent::Attachment { attachments } = &mut msg.content else {
+                continue;
+            };
+            let mut changed = false;
+            for att in attachments.iter_mut().filter(|att| att.view_once && !att.viewed) {
+                let Some(source) = att.local_path.as_deref().and_then(resolve_attachment_path) else {
+                    continue;
+                };
+                let Some(dest) = private_copy(&source) else {
+                    continue;
+                };
+                if !hand_off(&dest) {
+                    let _ = std::fs::remove_file(&dest);
+                    continue;
+                }
+                let _ = std::fs::remove_file(&source);
+                att.viewed = true;
+                att.local_path = None;
+                changed = true;
+                opened.push((msg.id.clone(), dest));
+            }
+            if changed {
+                let _ = storage.save_message(msg);
+            }
+        }
+        opened
+    }
+
     pub fn get_selected_attachment_paths(&self) -> Vec<String> {
         let Some(ref sel) = self.selection else {
             return Vec::new();
@@ -544,7 +1014,7 @@ impl ConversationView {
         for idx in sel.range() {
             if let Some(msg) = msgs.get(idx) {
                 if let MessageContent::Attachment { attachments } = &msg.content {
-                    for att in attachments {
+                    for att in attachments.iter().filter(|att| !att.view_once) {
                         if let Some(path) = &att.local_path {
                             paths.push(path.clone());
                         }
@@ -554,6 +1024,129 @@ impl ConversationView {
         }
         paths
     }
+
+    pub fn get_selected_audio_paths(&self) -> Vec<String> {
+        let (Some(sel), Some(msgs)) = (&self.selection, &self.messages) else {
+            return Vec::new();
+        };
+        attachments_of(sel.range().filter_map(|idx| msgs.get(idx)))
+            .filter(|att| !att.view_once && is_audio(att.content_type.as_deref()))
+            .filter_map(|att| att.local_path.clone())
+            .collect()
+    }
+}
+
+fn signal_color(name: &str) -> Option<Color> {
+    let color = match name.to_lowercase().replace('-', "_").as_str() {
+        "crimson" | "red" => Color::Red,
+        "vermilion" | "deep_orange" | "orange" => Color::LightRed,
+        "burlap" | "brown" | "taupe" => Color::Yellow,
+        "forest" | "green" => Color::Green,
+        "wintergreen" | "light_green" => Color::LightGreen,
+        "teal" | "cyan" => Color::Cyan,
+        "blue" | "light_blue" => Color::LightBlue,
+        "ultramarine" | "indigo" => Color::Blue,
+        "violet" | "purple" | "deep_purple" => Color::Magenta,
+        "plum" | "pink" => Color::LightMagenta,
+        "steel" | "blue_grey" | "grey" => Color::Gray,
+        _ => return None,
+    };
+    Some(color)
+}
+
+fn view_once_dir() -> PathBuf {
+    std::env::temp_dir().join("signal-tty-view-once")
+}
+
+pub fn remove_view_once_copies() {
+    let _ = std::fs::remove_dir_all(view_once_dir());
+}
+
+fn private_copy(source: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+    let dir = view_once_dir();
+    std::fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir).ok()?;
+    let ext = source
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let dest = dir.join(format!("{}{}", uuid::Uuid::new_v4(), ext));
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&dest)
+        .ok()?;
+    let copied = std::fs::File::open(source).and_then(|mut src| std::io::copy(&mut src, &mut file));
+    if copied.is_err() {
+        let _ = std::fs::remove_file(&dest);
+        return None;
+    }
+    Some(dest)
+}
+
+pub fn is_audio(content_type: Option<&str>) -> bool {
+    content_type.is_some_and(|ct| ct.starts_with("audio/"))
+}
+
+fn convert_quote(quote: &crate::infrastructure::Quote) -> Option<Quote> {
+    Some(Quote {
+        author_uuid: quote.author_uuid.clone().or_else(|| quote.author.clone())?,
+        timestamp: quote.id?,
+        text: quote.text.clone(),
+        attachment_name: quote
+            .attachments
+            .first()
+            .and_then(|a| a.filename.clone().or_else(|| a.content_type.clone())),
+    })
+}
+
+fn convert_preview(preview: &crate::infrastructure::Preview) -> LinkPreview {
+    LinkPreview {
+        url: preview.url.clone(),
+        title: preview.title.clone().filter(|t| !t.is_empty()),
+        description: preview.description.clone().filter(|d| !d.is_empty()),
+        image_path: preview.image.as_ref().and_then(|image| image.id.clone()),
+    }
+}
+
+fn attachments_of<'a>(
+    msgs: impl Iterator<Item = &'a Message>,
+) -> impl Iterator<Item = &'a AttachmentInfo> {
+    msgs.filter_map(|msg| match &msg.content {
+        MessageContent::Attachment { attachments } => Some(attachments),
+        _ => None,
+    })
+    .flatten()
+}
+
+pub fn resolve_attachment_path(path: &str) -> Option<PathBuf> {
+    if path.starts_with('/') {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("HOME").ok()?;
+    Some(
+        PathBuf::from(home)
+            .join(".local/share/signal-cli/attachments")
+            .join(path),
+    )
+}
+
+fn unique_destination(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(name);
+    let stem = path.file_stem().map_or(name.into(), |s| s.to_string_lossy());
+    let ext = path.extension().map(|e| e.to_string_lossy());
+    (1..)
+        .map(|n| match &ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
 }
 
 #[derive(Debug, Clone)]
@@ -568,6 +1161,87 @@ pub struct PendingRemoteDelete {
     pub timestamps: Vec<i64>,
 }
 
+pub struct Prompt {
+    pub message: String,
+    pub on_confirm: Box<dyn FnOnce(&mut App)>,
+}
+
+pub type TextPromptCallback = Box<dyn FnOnce(&mut App, String)>;
+
+pub struct TextPrompt {
+    pub title: String,
+    pub input: InputState,
+    pub on_submit: TextPromptCallback,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReactionGroup {
+    pub emoji: String,
+    pub senders: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedState {
+    pub selected_conversation: Option<String>,
+    pub focus: Option<Focus>,
+}
+
+pub struct PendingReply {
+    pub conversation_id: String,
+    pub quote: Quote,
+}
+
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub fn spinner_frame() -> &'static str {
+    let tick = now_millis() / SPINNER_INTERVAL.as_millis() as i64;
+    SPINNER_FRAMES[tick as usize % SPINNER_FRAMES.len()]
+}
+
+const VOICE_RECORDERS: [(&str, &[&str], &str); 2] = [
+    ("arecord", &["-q", "-f", "cd", "-t", "wav"], "wav"),
+    ("ffmpeg", &["-loglevel", "quiet", "-nostdin", "-f", "pulse", "-i", "default", "-ac", "1"], "m4a"),
+];
+
+const VOICE_NOTE_PREFIX: &str = "signal-tty-voice-";
+
+pub struct VoiceRecording {
+    child: Child,
+    pub path: PathBuf,
+    pub started: Instant,
+}
+
+impl VoiceRecording {
+    pub fn wait(mut self) -> PathBuf {
+        let _ = self.child.wait();
+        self.path.clone()
+    }
+}
+
+impl Drop for VoiceRecording {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+pub const ADMIN_ROLE: &str = "ADMINISTRATOR";
+pub const MEMBER_ROLE: &str = "DEFAULT";
+
+pub struct GroupRoster {
+    pub group_id: String,
+    pub members: Vec<GroupMember>,
+    pub is_admin: bool,
+    pub invite_link: Option<String>,
+}
+
+pub struct SafetyNumberView {
+    pub name: String,
+    pub safety_number: String,
+    pub scannable: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct LayoutAreas {
     pub conversations: Rect,
@@ -579,7 +1253,7 @@ pub struct LayoutAreas {
 
 pub struct App {
     pub storage: Arc<SqliteStorage>,
-    pub signal: SignalClient,
+    pub signal: Arc<SignalClient>,
     pub my_uuid: Option<String>,
     pub my_number: Option<String>,
 
@@ -590,15 +1264,74 @@ pub struct App {
     pub filter_input: InputState,
     pub file_browser: FileBrowserState,
     pub pending_attachments: Vec<PathBuf>,
+    pub voice_recording: Option<VoiceRecording>,
+    pub finishing_recording: Option<VoiceRecording>,
+    pub view_once_copies: Vec<(String, PathBuf)>,
 
     pub should_quit: bool,
     pub status_message: Option<String>,
     pub pending_send: Option<String>,
+    pub pending_reply: Option<PendingReply>,
+    pub pending_retry: Option<Message>,
+    pub pending_broadcast: Option<String>,
+    pub broadcast_targets: Vec<String>,
     pub pending_remote_deletes: Vec<PendingRemoteDelete>,
+    pub pending_typing: Vec<(SendTarget, bool)>,
+    pub pending_expiration: Option<(String, u32)>,
+    pub read_marking_deferred: bool,
+    pub send_read_receipts: bool,
+    pub refresh_avatars: bool,
+    pub pending_unregister: bool,
+    pub pending_account_list: bool,
+    pub pending_account_switch: Option<String>,
+    pub pending_number_lookup: Option<String>,
+    pub typing_target: Option<SendTarget>,
+    pub typing_sent_at: Option<Instant>,
+    pub typing: HashMap<String, (String, Instant)>,
+    pub recent_conversations: VecDeque<String>,
+    pub drafts: HashMap<String, String>,
+    pub group_member_counts: HashMap<String, usize>,
+    pub input_conversation: Option<String>,
     pub messages_height: usize,
     pub needs_image_preload: bool,
     pub pending_preload_paths: Vec<String>,
     pub show_empty_conversations: bool,
+    pub show_archived: bool,
+    pub list_density: ListDensity,
+    pub preview_length: usize,
+    pub max_message_width: Option<u16>,
+    pub timestamp_format: TimestampFormat,
+    pub reaction_details: Option<Vec<ReactionGroup>>,
+    pub group_roster: Option<GroupRoster>,
+    pub safety_number: Option<SafetyNumberView>,
+    pub settings_open: bool,
+    pub device_link: Option<String>,
+    pub osc52_clipboard: bool,
+    pub audio_player: Option<String>,
+    pub link_preview_images: bool,
+    pub notifications_enabled: bool,
+    pub bell_enabled: bool,
+    pub pending_bell: bool,
+    pub notification_privacy: NotificationPrivacy,
+    pub terminal_focused: bool,
+    pub pending_notifications: Vec<(String, String)>,
+    pub do_not_disturb: bool,
+    pub snooze_until: Option<Instant>,
+    pub state_path: Option<PathBuf>,
+    pub voice_notes_dir: Option<PathBuf>,
+    pub pending_group_fetch: Option<String>,
+    pub pending_invite_link: Option<String>,
+    pub text_prompt: Option<TextPrompt>,
+    pub prompt: Option<Prompt>,
+    pub connection_health: ConnectionHealth,
+    pub last_notification: Instant,
+    pub missed_heartbeats: u32,
+    pub registration: HashMap<String, Option<bool>>,
+    pub contacts: HashMap<String, Contact>,
+    pub identities: HashMap<String, Identity>,
+    pub verify_before_send: bool,
+    pub pending_identity_refresh: bool,
+    pub pending_trust: Option<String>,
 
     pub layout_areas: LayoutAreas,
     pub message_y_positions: Vec<(usize, u16, u16)>,
@@ -607,7 +1340,7 @@ pub struct App {
 impl App {
     pub fn new(
         storage: Arc<SqliteStorage>,
-        signal: SignalClient,
+        signal: Arc<SignalClient>,
         my_number: Option<String>,
     ) -> Self {
         Self {
@@ -622,14 +1355,73 @@ impl App {
             filter_input: InputState::default(),
             file_browser: FileBrowserState::default(),
             pending_attachments: Vec::new(),
+            voice_recording: None,
+            finishing_recording: None,
+            view_once_copies: Vec::new(),
             should_quit: false,
             status_message: None,
             pending_send: None,
+            pending_reply: None,
+            pending_retry: None,
+            pending_broadcast: None,
+            broadcast_targets: Vec::new(),
             pending_remote_deletes: Vec::new(),
+            pending_typing: Vec::new(),
+            pending_expiration: None,
+            read_marking_deferred: true,
+            send_read_receipts: true,
+            refresh_avatars: false,
+            pending_unregister: false,
+            pending_account_list: false,
+            pending_account_switch: None,
+            pending_number_lookup: None,
+            typing_target: None,
+            typing_sent_at: None,
+            typing: HashMap::new(),
+            recent_conversations: VecDeque::new(),
+            drafts: HashMap::new(),
+            group_member_counts: HashMap::new(),
+            input_conversation: None,
             messages_height: 20,
             needs_image_preload: false,
             pending_preload_paths: Vec::new(),
             show_empty_conversations: false,
+            show_archived: false,
+            list_density: ListDensity::default(),
+            preview_length: DEFAULT_PREVIEW_LENGTH,
+            max_message_width: None,
+            timestamp_format: TimestampFormat::default(),
+            reaction_details: None,
+            group_roster: None,
+            safety_number: None,
+            settings_open: false,
+            device_link: None,
+            osc52_clipboard: false,
+            audio_player: None,
+            link_preview_images: false,
+            notifications_enabled: false,
+            bell_enabled: false,
+            pending_bell: false,
+            notification_privacy: NotificationPrivacy::default(),
+            terminal_focused: true,
+            pending_notifications: Vec::new(),
+            do_not_disturb: false,
+            snooze_until: None,
+            state_path: None,
+            voice_notes_dir: None,
+            pending_group_fetch: None,
+            pending_invite_link: None,
+            text_prompt: None,
+            prompt: None,
+            connection_health: ConnectionHealth::Healthy,
+            last_notification: Instant::now(),
+            missed_heartbeats: 0,
+            registration: HashMap::new(),
+            contacts: HashMap::new(),
+            identities: HashMap::new(),
+            verify_before_send: true,
+            pending_identity_refresh: false,
+            pending_trust: None,
             layout_areas: LayoutAreas::default(),
             message_y_positions: Vec::new(),
         }
@@ -641,20 +1433,172 @@ impl App {
                 .into_iter()
                 .map(|c| ConversationView::new(c, &self.storage))
                 .collect();
-            let first_with_messages = self
-                .conversations
-                .iter()
-                .position(|c| c.conversation.last_message_timestamp.is_some());
+            let saved = self.load_saved_state();
+            let restored = saved.selected_conversation.and_then(|id| {
+                self.conversations
+                    .iter()
+                    .position(|c| c.conversation.id == id)
+            });
+            if restored.is_some()
+                && let Some(focus) = saved.focus
+                && matches!(focus, Focus::Conversations | Focus::Messages | Focus::Input)
+            {
+                self.focus = focus;
+            }
+            let first_with_messages = restored.or_else(|| {
+                self.conversations
+                    .iter()
+                    .position(|c| c.conversation.last_message_timestamp.is_some())
+            });
             if let Some(idx) = first_with_messages {
                 self.selected = idx;
-                if self.conversations[idx].load_messages(&self.storage) {
-                    self.needs_image_preload = true;
-                }
+                self.load_selected_messages();
             }
         }
     }
 
-    pub fn take_preload_paths(&mut self) -> Vec<String> {
+    pub fn load_selected_messages(&mut self) {
+        let Some(conv) = self.conversations.get_mut(self.selected) else {
+            return;
+        };
+        if conv.load_messages(&self.storage) {
+            self.needs_image_preload = true;
+        }
+        let id = conv.conversation.id.clone();
+        let group_id = conv.conversation.group_id.clone();
+        self.swap_draft(&id);
+        if let Some(group_id) = group_id {
+            self.ensure_group_member_count(group_id);
+        }
+        self.recent_conversations.retain(|c| *c != id);
+        self.recent_conversations.push_front(id);
+        while self.recent_conversations.len() > MAX_LOADED_CONVERSATIONS {
+            let conversations = &self.conversations;
+            let Some(pos) = self.recent_conversations.iter().skip(1).rposition(|id| {
+                conversations
+                    .iter()
+                    .find(|c| c.conversation.id == *id)
+                    .is_none_or(|c| c.sending.is_empty())
+            }) else {
+                break;
+            };
+            let Some(stale) = self.recent_conversations.remove(pos + 1) else {
+                break;
+            };
+            if let Some(conv) = self.conversations.iter_mut().find(|c| c.conversation.id == stale) {
+                conv.unload_messages();
+            }
+        }
+    }
+
+    pub fn load_settings(&mut self) {
+        let setting = |key: &str| self.storage.get_setting(key).ok().flatten();
+        if let Some(value) = setting("show_empty_conversations") {
+            self.show_empty_conversations = value == "true";
+        }
+        if let Some(density) = setting("list_density").and_then(|v| ListDensity::parse(&v)) {
+            self.list_density = density;
+        }
+        if let Some(value) = setting("send_read_receipts") {
+            self.send_read_receipts = value == "true";
+        }
+        if let Some(privacy) = setting("notification_privacy").and_then(|v| NotificationPrivacy::parse(&v)) {
+            self.notification_privacy = privacy;
+        }
+        if setting("account").is_none()
+            && let Some(number) = self.my_number.clone()
+        {
+            self.save_setting("account", &number);
+        }
+    }
+
+    pub fn save_setting(&mut self, key: &str, value: &str) {
+        if let Err(e) = self.storage.set_setting(key, value) {
+            self.status_message = Some(format!("Failed to save setting: {}", e));
+        }
+    }
+
+    pub fn toggle_empty_conversations(&mut self) {
+        self.show_empty_conversations = !self.show_empty_conversations;
+        let value = self.show_empty_conversations.to_string();
+        self.save_setting("show_empty_conversations", &value);
+        self.ensure_selection_matches_filter();
+    }
+
+    pub fn toggle_list_density(&mut self) {
+        self.list_density = self.list_density.toggled();
+        self.save_setting("list_density", self.list_density.as_str());
+    }
+
+    pub fn toggle_read_receipts(&mut self) {
+        self.send_read_receipts = !self.send_read_receipts;
+        let value = self.send_read_receipts.to_string();
+        self.save_setting("send_read_receipts", &value);
+    }
+
+    fn ensure_group_member_count(&mut self, group_id: String) {
+        if self.group_member_counts.contains_key(&group_id) {
+            return;
+        }
+        match self.storage.get_group_members(&group_id) {
+            Ok(members) if !members.is_empty() => {
+                self.group_member_counts.insert(group_id, members.len());
+            }
+            _ => {
+                if self.pending_group_fetch.is_none() {
+                    self.pending_group_fetch = Some(group_id);
+                }
+            }
+        }
+    }
+
+    fn swap_draft(&mut self, conversation_id: &str) {
+        if self.input_conversation.as_deref() == Some(conversation_id) || !self.broadcast_targets.is_empty() {
+            return;
+        }
+        let text = self.input.clear();
+        if let Some(previous) = self.input_conversation.take()
+            && !text.is_empty()
+        {
+            self.drafts.insert(previous, text);
+        }
+        if let Some(draft) = self.drafts.remove(conversation_id) {
+            self.input.insert_str(&draft);
+        }
+        self.input_conversation = Some(conversation_id.to_string());
+    }
+
+    pub fn draft_for(&self, conversation_id: &str) -> Option<&str> {
+        let draft = if self.input_conversation.as_deref() == Some(conversation_id) && self.broadcast_targets.is_empty() {
+            self.input.text.as_str()
+        } else {
+            self.drafts.get(conversation_id)?.as_str()
+        };
+        (!draft.is_empty()).then_some(draft)
+    }
+
+    fn load_saved_state(&self) -> SavedState {
+        self.state_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_state(&self) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+        let state = SavedState {
+            selected_conversation: self.selected_conversation().map(|c| c.conversation.id.clone()),
+            focus: Some(self.focus),
+        };
+        if let Ok(json) = serde_json::to_string(&state) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    pub fn take_preload_paths(&mut self) -> Vec<String> {
         let mut paths = std::mem::take(&mut self.pending_preload_paths);
 
         if self.needs_image_preload {
@@ -690,9 +1634,7 @@ impl App {
         };
         if let Some(&new_idx) = indices.get(new_pos) {
             self.selected = new_idx;
-            if self.conversations[self.selected].load_messages(&self.storage) {
-                self.needs_image_preload = true;
-            }
+            self.load_selected_messages();
         }
     }
 
@@ -708,39 +1650,1135 @@ impl App {
         };
         if let Some(&new_idx) = indices.get(new_pos) {
             self.selected = new_idx;
-            if self.conversations[self.selected].load_messages(&self.storage) {
-                self.needs_image_preload = true;
+            self.load_selected_messages();
+        }
+    }
+
+    pub fn scroll_messages_up(&mut self) {
+        self.scroll_messages_up_by(SCROLL_LINES);
+    }
+
+    pub fn scroll_messages_down(&mut self) {
+        self.scroll_messages_down_by(SCROLL_LINES);
+    }
+
+    pub fn page_messages_up(&mut self) {
+        self.scroll_messages_up_by(self.messages_height.max(1));
+    }
+
+    pub fn page_messages_down(&mut self) {
+        self.scroll_messages_down_by(self.messages_height.max(1));
+    }
+
+    fn scroll_messages_up_by(&mut self, lines: usize) {
+        let storage = self.storage.clone();
+
+        if let Some(conv) = self.selected_conversation_mut() {
+            conv.scroll_offset = conv.scroll_offset.saturating_add(lines);
+
+            // Check if we need to load more messages
+            if conv.has_more_messages {
+                let paths = conv.load_older_messages(&storage);
+                if !paths.is_empty() {
+                    self.pending_preload_paths.extend(paths);
+                }
             }
         }
     }
 
-    pub fn scroll_messages_up(&mut self) {
-        let storage = self.storage.clone();
+    fn scroll_messages_down_by(&mut self, lines: usize) {
+        if let Some(conv) = self.selected_conversation_mut() {
+            conv.scroll_offset = conv.scroll_offset.saturating_sub(lines);
+        }
+    }
+
+    pub fn scroll_messages_to_top(&mut self) {
+        let storage = self.storage.clone();
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        let at_top = conv.visible_range.is_some_and(|(start, _)| start == 0);
+        let paths = if at_top {
+            conv.load_older_messages(&storage)
+        } else {
+            Vec::new()
+        };
+        conv.scroll_offset = usize::MAX;
+        self.pending_preload_paths.extend(paths);
+    }
+
+    pub fn jump_to_quoted_message(&mut self) {
+        let storage = self.storage.clone();
+        let Some(author) = self
+            .selected_conversation()
+            .and_then(|c| c.selected_quote())
+            .map(|q| q.author_uuid.clone())
+            .filter(|a| !a.is_empty())
+        else {
+            return;
+        };
+        let mut authors = vec![author.clone()];
+        if let Some(contact) = self.contacts.get(&author) {
+            authors.extend([&contact.uuid, &contact.number].into_iter().flatten().cloned());
+        }
+        let mine = [&self.my_uuid, &self.my_number]
+            .into_iter()
+            .flatten()
+            .any(|id| authors.contains(id));
+        if mine {
+            authors.extend([&self.my_uuid, &self.my_number].into_iter().flatten().cloned());
+        }
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        match conv.jump_to_quoted(&storage, &authors, mine) {
+            Some(paths) => self.pending_preload_paths.extend(paths),
+            None => self.status_message = Some("Quoted message not found".to_string()),
+        }
+    }
+
+    pub fn start_go_to_date(&mut self) {
+        if self.selected_conversation().is_none() {
+            return;
+        }
+        self.prompt_text("Go to date (YYYY-MM-DD or YYYY-MM-DD HH:MM)", String::new(), |app, text| {
+            let Some(timestamp) = parse_date(&text) else {
+                app.status_message = Some(format!("Invalid date: {}", text.trim()));
+                return;
+            };
+            let storage = app.storage.clone();
+            let Some(conv) = app.selected_conversation_mut() else {
+                return;
+            };
+            match conv.jump_to_date(&storage, timestamp) {
+                Some(paths) => {
+                    app.pending_preload_paths.extend(paths);
+                    app.focus = Focus::Messages;
+                }
+                None => app.status_message = Some(format!("No messages on or after {}", text.trim())),
+            }
+        });
+    }
+
+    pub fn show_reaction_details(&mut self) {
+        let Some(message_id) = self.selected_conversation().and_then(|c| {
+            let sel = c.selection.as_ref()?;
+            c.messages.as_ref()?.get(sel.cursor).map(|m| m.id.clone())
+        }) else {
+            return;
+        };
+
+        let reactions = match self.storage.get_reactions(&message_id) {
+            Ok(reactions) => reactions,
+            Err(e) => {
+                self.status_message = Some(format!("Failed to load reactions: {}", e));
+                return;
+            }
+        };
+        if reactions.is_empty() {
+            self.status_message = Some("No reactions".to_string());
+            return;
+        }
+
+        self.reaction_details = Some(self.group_reactions(reactions));
+    }
+
+    fn group_reactions(&self, mut reactions: Vec<Reaction>) -> Vec<ReactionGroup> {
+        reactions.sort_by_key(|r| r.timestamp);
+        let mut groups: Vec<ReactionGroup> = Vec::new();
+        for reaction in reactions {
+            let name = self.contact_name(&reaction.sender_uuid);
+            match groups.iter_mut().find(|g| g.emoji == reaction.emoji) {
+                Some(group) => group.senders.push(name),
+                None => groups.push(ReactionGroup {
+                    emoji: reaction.emoji,
+                    senders: vec![name],
+                }),
+            }
+        }
+        groups.sort_by_key(|g| std::cmp::Reverse(g.senders.len()));
+        groups
+    }
+
+    pub fn open_group_roster(&mut self) {
+        let Some(group_id) = self
+            .selected_conversation()
+            .and_then(|c| c.conversation.group_id.clone())
+        else {
+            return;
+        };
+        self.show_group_roster(&group_id);
+        self.pending_group_fetch = Some(group_id);
+    }
+
+    pub fn show_group_roster(&mut self, group_id: &str) {
+        let mut members = self.storage.get_group_members(group_id).unwrap_or_default();
+        members.sort_by_key(|m| {
+            (
+                m.role.as_deref() != Some(ADMIN_ROLE),
+                m.member_name.clone().unwrap_or_default().to_lowercase(),
+            )
+        });
+        let invite_link = self
+            .group_roster
+            .take()
+            .filter(|r| r.group_id == group_id)
+            .and_then(|r| r.invite_link);
+        self.group_roster = Some(GroupRoster {
+            group_id: group_id.to_string(),
+            is_admin: self.is_group_admin(group_id),
+            members,
+            invite_link,
+        });
+    }
+
+    pub fn request_invite_link(&mut self) {
+        let Some(roster) = &self.group_roster else {
+            return;
+        };
+        if roster.is_admin {
+            self.pending_invite_link = Some(roster.group_id.clone());
+        } else {
+            self.status_message = Some("Only group admins can create invite links".to_string());
+        }
+    }
+
+    pub fn show_invite_link(&mut self, group_id: &str, link: String) {
+        if let Some(roster) = self.group_roster.as_mut().filter(|r| r.group_id == group_id) {
+            roster.invite_link = Some(link);
+        }
+    }
+
+    pub fn is_group_admin(&self, group_id: &str) -> bool {
+        self.storage
+            .get_group_members(group_id)
+            .unwrap_or_default()
+            .iter()
+            .any(|m| m.role.as_deref() == Some(ADMIN_ROLE) && self.is_me(&m.member_uuid))
+    }
+
+    fn is_me(&self, identifier: &str) -> bool {
+        self.my_uuid.as_deref() == Some(identifier) || self.my_number.as_deref() == Some(identifier)
+    }
+
+    pub fn apply_directory(&mut self, contacts: Vec<Contact>, groups: Vec<Group>) {
+        self.set_contacts(contacts);
+        let mut changed = Vec::new();
+        for view in &mut self.conversations {
+            let conv = &mut view.conversation;
+            let name = match conv.conversation_type {
+                ConversationType::Direct => [&conv.recipient_uuid, &conv.recipient_number]
+                    .into_iter()
+                    .flatten()
+                    .find_map(|key| self.contacts.get(key))
+                    .and_then(|c| c.profile_name.clone().or_else(|| c.name.clone()))
+                    .filter(|n| !n.is_empty()),
+                ConversationType::Group => groups
+                    .iter()
+                    .find(|g| conv.group_id.as_ref() == Some(&g.id))
+                    .and_then(|g| g.name.clone())
+                    .filter(|n| !n.is_empty()),
+            };
+            let current = match conv.conversation_type {
+                ConversationType::Direct => &mut conv.recipient_name,
+                ConversationType::Group => &mut conv.group_name,
+            };
+            if name.is_some() && *current != name {
+                *current = name;
+                changed.push(conv.clone());
+            }
+        }
+        for conv in &changed {
+            let _ = self.storage.update_conversation(conv);
+        }
+        for group in groups.iter().filter(|g| g.is_member) {
+            self.apply_group_details(group);
+        }
+    }
+
+    pub fn apply_group_details(&mut self, group: &Group) {
+        let members: Vec<GroupMember> = group
+            .members
+            .iter()
+            .filter_map(|member| {
+                if self.my_uuid.is_none() && member.number.is_some() && member.number == self.my_number {
+                    self.my_uuid = member.uuid.clone();
+                }
+                let member_uuid = member.identifier()?;
+                let role = if group.admins.contains(member) { ADMIN_ROLE } else { MEMBER_ROLE };
+                Some(GroupMember {
+                    group_id: group.id.clone(),
+                    member_name: Some(self.member_name(member.uuid.as_deref(), member.number.as_deref())),
+                    member_uuid,
+                    role: Some(role.to_string()),
+                })
+            })
+            .collect();
+        let _ = self.storage.save_group_members(&group.id, &members);
+        self.group_member_counts.insert(group.id.clone(), members.len());
+        if self
+            .group_roster
+            .as_ref()
+            .is_some_and(|r| r.group_id == group.id)
+        {
+            self.show_group_roster(&group.id);
+        }
+    }
+
+    fn member_name(&self, uuid: Option<&str>, number: Option<&str>) -> String {
+        if let Some(contact) = [uuid, number]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.contacts.get(key))
+        {
+            return contact.display_name();
+        }
+        match (uuid, number) {
+            (Some(uuid), _) => self.contact_name(uuid),
+            (None, Some(number)) => number.to_string(),
+            (None, None) => "Unknown".to_string(),
+        }
+    }
+
+    pub fn contact_name(&self, uuid: &str) -> String {
+        if self.my_uuid.as_deref() == Some(uuid) || self.my_number.as_deref() == Some(uuid) {
+            return "You".to_string();
+        }
+
+        if let Some(conv) = self.conversations.iter().find(|c| {
+            c.conversation.recipient_uuid.as_deref() == Some(uuid)
+                || c.conversation.recipient_number.as_deref() == Some(uuid)
+        }) {
+            return conv.conversation.display_name();
+        }
+
+        self.conversations
+            .iter()
+            .filter_map(|c| c.messages.as_ref())
+            .flatten()
+            .find(|m| m.sender_uuid == uuid && m.sender_name.is_some())
+            .and_then(|m| m.sender_name.clone())
+            .unwrap_or_else(|| uuid.to_string())
+    }
+
+    pub fn set_contacts(&mut self, contacts: Vec<Contact>) {
+        self.contacts.clear();
+        for contact in contacts {
+            for key in [&contact.uuid, &contact.number].into_iter().flatten() {
+                self.contacts.insert(key.clone(), contact.clone());
+            }
+        }
+    }
+
+    pub fn set_identities(&mut self, identities: Vec<Identity>) {
+        self.identities.clear();
+        for identity in identities {
+            for key in [&identity.uuid, &identity.number].into_iter().flatten() {
+                self.identities.insert(key.clone(), identity.clone());
+            }
+        }
+    }
+
+    fn identity_for(&self, conversation: &Conversation) -> Option<&Identity> {
+        [&conversation.recipient_uuid, &conversation.recipient_number]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.identities.get(key))
+    }
+
+    pub fn untrusted_identity(&self, conversation: &Conversation) -> Option<&Identity> {
+        self.identity_for(conversation)
+            .filter(|identity| identity.is_untrusted())
+    }
+
+    pub fn open_safety_number(&mut self) {
+        let Some(conversation) = self
+            .selected_conversation()
+            .map(|c| c.conversation.clone())
+            .filter(|c| c.conversation_type == ConversationType::Direct)
+        else {
+            return;
+        };
+        let name = conversation.display_name();
+        let view = self.identity_for(&conversation).and_then(|identity| {
+            Some(SafetyNumberView {
+                name: name.clone(),
+                safety_number: identity.safety_number.clone()?,
+                scannable: identity.scannable_safety_number.clone(),
+            })
+        });
+        match view {
+            Some(view) => self.safety_number = Some(view),
+            None => {
+                self.status_message = Some(format!("No safety number known for {}", name));
+                self.pending_identity_refresh = true;
+            }
+        }
+    }
+
+    pub fn contact_about(&self, conversation: &Conversation) -> Option<String> {
+        [&conversation.recipient_uuid, &conversation.recipient_number]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.contacts.get(key))
+            .and_then(|c| c.about_line())
+    }
+
+    pub fn is_registered(&self, number: &str) -> Option<bool> {
+        self.registration.get(number).copied().flatten()
+    }
+
+    pub fn take_unchecked_recipients(&mut self) -> Vec<String> {
+        let mut numbers = Vec::new();
+        for conv in &self.conversations {
+            if conv.conversation.conversation_type != ConversationType::Direct {
+                continue;
+            }
+            let Some(number) = conv.conversation.recipient_number.as_ref() else {
+                continue;
+            };
+            if self.my_number.as_ref() == Some(number) || self.registration.contains_key(number) {
+                continue;
+            }
+            numbers.push(number.clone());
+        }
+        for number in &numbers {
+            self.registration.insert(number.clone(), None);
+        }
+        numbers
+    }
+
+    pub fn record_user_status(&mut self, statuses: Vec<UserStatus>) {
+        for status in statuses {
+            let number = status.number.unwrap_or(status.recipient);
+            self.registration.insert(number, Some(status.is_registered));
+        }
+    }
+
+    pub fn record_heartbeat(&mut self, ok: bool) -> ConnectionHealth {
+        if ok {
+            self.missed_heartbeats = 0;
+            self.connection_health = ConnectionHealth::Healthy;
+        } else {
+            self.missed_heartbeats += 1;
+            self.connection_health = if self.missed_heartbeats >= MAX_MISSED_HEARTBEATS {
+                ConnectionHealth::Unhealthy
+            } else {
+                ConnectionHealth::Degraded
+            };
+        }
+        self.connection_health
+    }
+
+    pub fn canonical_recipient(&self, id: &str) -> String {
+        self.contacts
+            .get(id)
+            .and_then(|contact| contact.uuid.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn record_delivery(&mut self, message: &Message, recipient: &str, state: DeliveryState) {
+        let recipient = self.canonical_recipient(recipient);
+        let status = DeliveryStatus {
+            message_id: message.id.clone(),
+            recipient_uuid: recipient.clone(),
+            state,
+            updated_at: now_millis(),
+        };
+        let _ = self.storage.save_delivery_status(&status);
+        if let Some(conv) = self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == message.conversation_id)
+        {
+            let statuses = conv.deliveries.entry(message.id.clone()).or_default();
+            statuses.retain(|s| s.recipient_uuid != recipient);
+            statuses.push(status);
+            if state == DeliveryState::Failed {
+                conv.failed_sends.insert(message.id.clone());
+            } else {
+                conv.failed_sends.remove(&message.id);
+            }
+            if state == DeliveryState::Sending {
+                conv.sending.insert(message.id.clone());
+            } else {
+                conv.sending.remove(&message.id);
+            }
+        }
+    }
+
+    pub fn reconcile_sent_message(&mut self, message: &Message) {
+        let _ = self.storage.save_message(message);
+        let Some(conv) = self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == message.conversation_id)
+        else {
+            return;
+        };
+        let Some(msgs) = conv.messages.as_mut() else {
+            return;
+        };
+        match msgs.iter_mut().find(|m| m.id == message.id) {
+            Some(m) => m.timestamp = message.timestamp,
+            None => conv.add_message(message.clone()),
+        }
+    }
+
+    pub fn has_pending_sends(&self) -> bool {
+        self.conversations.iter().any(|c| !c.sending.is_empty())
+    }
+
+    pub fn retry_selected_send(&mut self) {
+        let Some(msg) = self
+            .selected_conversation()
+            .and_then(|c| c.selected_failed_message())
+            .cloned()
+        else {
+            return;
+        };
+        if let Some(conv) = self.selected_conversation_mut() {
+            conv.failed_sends.remove(&msg.id);
+            conv.exit_selection_mode();
+        }
+        self.pending_retry = Some(msg);
+    }
+
+    pub fn confirm(&mut self, message: impl Into<String>, on_confirm: impl FnOnce(&mut App) + 'static) {
+        self.prompt = Some(Prompt {
+            message: message.into(),
+            on_confirm: Box::new(on_confirm),
+        });
+    }
+
+    pub fn resolve_prompt(&mut self, confirmed: bool) {
+        if let Some(prompt) = self.prompt.take()
+            && confirmed
+        {
+            (prompt.on_confirm)(self);
+        }
+    }
+
+    pub fn remote_delete_selected(&mut self) {
+        let storage = self.storage.clone();
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        let timestamps = conv.get_selected_outgoing_timestamps();
+        let target = conv.remote_delete_target();
+        let ids = conv.delete_selected_messages();
+        for id in &ids {
+            let _ = storage.delete_message(id);
+        }
+        if let Some(target) = target
+            && !timestamps.is_empty()
+        {
+            self.pending_remote_deletes.push(PendingRemoteDelete { target, timestamps });
+        }
+    }
+
+    pub fn prompt_text(
+        &mut self,
+        title: impl Into<String>,
+        initial: impl Into<String>,
+        on_submit: impl FnOnce(&mut App, String) + 'static,
+    ) {
+        let text = initial.into();
+        let cursor = text.len();
+        self.text_prompt = Some(TextPrompt {
+            title: title.into(),
+            input: InputState { text, cursor },
+            on_submit: Box::new(on_submit),
+        });
+    }
+
+    pub fn submit_text_prompt(&mut self) {
+        if let Some(prompt) = self.text_prompt.take() {
+            (prompt.on_submit)(self, prompt.input.text);
+        }
+    }
+
+    pub fn start_alias_edit(&mut self) {
+        let Some(conv) = self.selected_conversation() else {
+            return;
+        };
+        let conv_id = conv.conversation.id.clone();
+        let current = conv.conversation.local_alias.clone().unwrap_or_default();
+        self.prompt_text("Local alias (empty to clear)", current, move |app, text| {
+            app.set_local_alias(&conv_id, text.trim());
+        });
+    }
+
+    pub fn open_account_picker(&mut self, accounts: Vec<String>) {
+        let others: Vec<String> = accounts
+            .into_iter()
+            .filter(|a| self.my_number.as_ref() != Some(a))
+            .collect();
+        if others.is_empty() {
+            self.status_message = Some("No other accounts".to_string());
+            return;
+        }
+        let title = format!("Switch account ({})", others.join(", "));
+        let initial = if others.len() == 1 { others[0].clone() } else { String::new() };
+        self.prompt_text(title, initial, move |app, text| {
+            let choice = text.trim();
+            if others.iter().any(|a| a == choice) {
+                app.pending_account_switch = Some(choice.to_string());
+            } else {
+                app.status_message = Some(format!("Unknown account: {}", choice));
+            }
+        });
+    }
+
+    pub fn switch_account(
+        &mut self,
+        storage: Arc<SqliteStorage>,
+        state_path: PathBuf,
+        signal: Arc<SignalClient>,
+        account: String,
+    ) {
+        self.save_state();
+        self.state_path = Some(state_path);
+        self.storage = storage;
+        self.signal = signal;
+        self.my_number = Some(account);
+        self.my_uuid = None;
+        self.conversations.clear();
+        self.recent_conversations.clear();
+        self.drafts.clear();
+        self.group_member_counts.clear();
+        self.input_conversation = None;
+        self.input.clear();
+        self.discard_pending_attachments();
+        self.dismiss_view_once();
+        self.load_settings();
+        self.selected = 0;
+        self.focus = Focus::Conversations;
+        self.filter_input.clear();
+        self.broadcast_targets.clear();
+        self.pending_send = None;
+        self.pending_reply = None;
+        self.pending_broadcast = None;
+        self.pending_retry = None;
+        self.pending_remote_deletes.clear();
+        self.pending_typing.clear();
+        self.typing_target = None;
+        self.typing_sent_at = None;
+        self.pending_expiration = None;
+        self.pending_group_fetch = None;
+        self.pending_invite_link = None;
+        self.pending_number_lookup = None;
+        self.pending_notifications.clear();
+        self.pending_preload_paths.clear();
+        self.typing.clear();
+        self.group_roster = None;
+        self.safety_number = None;
+        self.reaction_details = None;
+        self.device_link = None;
+        self.text_prompt = None;
+        self.prompt = None;
+        self.connection_health = ConnectionHealth::Healthy;
+        self.missed_heartbeats = 0;
+        self.registration.clear();
+        self.identities.clear();
+        self.pending_trust = None;
+        self.contacts.clear();
+        self.load_conversations();
+    }
+
+    pub fn start_unregister(&mut self) {
+        let Some(number) = self.my_number.clone() else {
+            self.status_message = Some("Unknown account number".to_string());
+            return;
+        };
+        self.prompt_text(
+            "Unregister this account and delete all local data? Type the account number to confirm",
+            String::new(),
+            move |app, text| {
+                if text.trim() == number {
+                    app.pending_unregister = true;
+                } else {
+                    app.status_message = Some("Unregister cancelled".to_string());
+                }
+            },
+        );
+    }
+
+    pub fn start_expiration_edit(&mut self) {
+        let Some(conv) = self.selected_conversation() else {
+            return;
+        };
+        let conv_id = conv.conversation.id.clone();
+        let current = match conv.conversation.expiration_seconds {
+            0 => String::new(),
+            secs => format_duration(secs),
+        };
+        self.prompt_text(
+            "Disappearing messages (e.g. 30s, 5m, 1h, 1d, 1w; empty to disable)",
+            current,
+            move |app, text| match parse_duration(&text) {
+                Some(seconds) => app.pending_expiration = Some((conv_id, seconds)),
+                None => app.status_message = Some(format!("Invalid duration: {}", text.trim())),
+            },
+        );
+    }
+
+    pub fn apply_expiration(&mut self, conversation: &Conversation, seconds: u32) {
+        let mut updated = match self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == conversation.id)
+        {
+            Some(view) => {
+                if view.conversation.expiration_seconds == seconds {
+                    return;
+                }
+                view.conversation.expiration_seconds = seconds;
+                view.conversation.clone()
+            }
+            None if conversation.expiration_seconds == seconds => return,
+            None => conversation.clone(),
+        };
+        updated.expiration_seconds = seconds;
+        if let Err(e) = self.storage.update_conversation(&updated) {
+            self.status_message = Some(format!("Failed to save conversation: {}", e));
+        }
+    }
+
+    pub fn toggle_selected_archived(&mut self) {
+        let storage = self.storage.clone();
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        conv.conversation.is_archived = !conv.conversation.is_archived;
+        let archived = conv.conversation.is_archived;
+        if let Err(e) = storage.update_conversation(&conv.conversation) {
+            self.status_message = Some(format!("Failed to save conversation: {}", e));
+            return;
+        }
+        self.status_message = Some(if archived { "Archived" } else { "Unarchived" }.to_string());
+        self.ensure_selection_matches_filter();
+    }
+
+    pub fn toggle_selected_muted(&mut self) {
+        let storage = self.storage.clone();
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        conv.conversation.is_muted = !conv.conversation.is_muted;
+        let muted = conv.conversation.is_muted;
+        if let Err(e) = storage.update_conversation(&conv.conversation) {
+            self.status_message = Some(format!("Failed to save conversation: {}", e));
+            return;
+        }
+        self.status_message = Some(if muted { "Muted" } else { "Unmuted" }.to_string());
+    }
+
+    pub fn total_unread(&self) -> u32 {
+        self.conversations
+            .iter()
+            .filter(|c| !c.conversation.is_muted)
+            .map(|c| c.unread_count())
+            .sum()
+    }
+
+    pub fn start_new_conversation(&mut self) {
+        self.prompt_text("Message phone number (+E.164)", "+".to_string(), |app, text| {
+            match normalize_e164(text.trim()) {
+                Some(number) => {
+                    app.status_message = Some(format!("Looking up {}...", number));
+                    app.pending_number_lookup = Some(number);
+                }
+                None => app.status_message = Some(format!("Not a valid phone number: {}", text.trim())),
+            }
+        });
+    }
+
+    pub fn apply_number_lookup(&mut self, number: &str, statuses: Vec<UserStatus>) {
+        let uuid = statuses
+            .iter()
+            .find(|s| s.is_registered && (s.number.as_deref() == Some(number) || s.recipient == number))
+            .and_then(|s| s.uuid.clone());
+        self.record_user_status(statuses);
+        match uuid {
+            Some(uuid) => {
+                self.status_message = None;
+                self.open_direct_conversation(&uuid, number);
+            }
+            None => self.status_message = Some(format!("{} is not on Signal", number)),
+        }
+    }
+
+    fn open_direct_conversation(&mut self, uuid: &str, number: &str) {
+        let conversation = match self
+            .storage
+            .get_or_create_direct_conversation(uuid, Some(number), None)
+        {
+            Ok(conversation) => conversation,
+            Err(e) => {
+                self.status_message = Some(format!("Failed to open conversation: {}", e));
+                return;
+            }
+        };
+        if !self.conversations.iter().any(|c| c.conversation.id == conversation.id) {
+            self.insert_conversation(&conversation.id);
+        }
+        let Some(idx) = self
+            .conversations
+            .iter()
+            .position(|c| c.conversation.id == conversation.id)
+        else {
+            return;
+        };
+        self.filter_input.clear();
+        self.show_archived |= conversation.is_archived;
+        self.show_empty_conversations |= conversation.last_message_timestamp.is_none();
+        self.selected = idx;
+        self.load_selected_messages();
+        self.focus = Focus::Input;
+    }
+
+    pub fn start_color_edit(&mut self) {
+        let Some(conv) = self.selected_conversation() else {
+            return;
+        };
+        let conv_id = conv.conversation.id.clone();
+        let current = conv.conversation.color.clone().unwrap_or_default();
+        self.prompt_text("Color name or #rrggbb (empty to clear)", current, move |app, text| {
+            let text = text.trim();
+            if !text.is_empty() && text.parse::<Color>().is_err() {
+                app.status_message = Some(format!("Unknown color: {}", text));
+                return;
+            }
+            app.set_conversation_color(&conv_id, text);
+        });
+    }
+
+    fn set_conversation_color(&mut self, conv_id: &str, color: &str) {
+        let color = (!color.is_empty()).then(|| color.to_string());
+        let storage = self.storage.clone();
+        let Some(conv) = self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == conv_id)
+        else {
+            return;
+        };
+        conv.conversation.color = color;
+        if let Err(e) = storage.update_conversation(&conv.conversation) {
+            self.status_message = Some(format!("Failed to save color: {}", e));
+        }
+    }
+
+    pub fn conversation_color(&self, conversation: &Conversation) -> Option<Color> {
+        if let Some(color) = conversation.color.as_deref().and_then(|c| c.parse().ok()) {
+            return Some(color);
+        }
+        if conversation.conversation_type != ConversationType::Direct {
+            return None;
+        }
+        [&conversation.recipient_uuid, &conversation.recipient_number]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.contact_color(key))
+    }
+
+    pub fn contact_color(&self, id: &str) -> Option<Color> {
+        signal_color(self.contacts.get(id)?.color.as_deref()?)
+    }
+
+    fn set_local_alias(&mut self, conv_id: &str, alias: &str) {
+        let alias = (!alias.is_empty()).then(|| alias.to_string());
+        let storage = self.storage.clone();
+        let Some(conv) = self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == conv_id)
+        else {
+            return;
+        };
+        conv.conversation.local_alias = alias;
+        if let Err(e) = storage.update_conversation(&conv.conversation) {
+            self.status_message = Some(format!("Failed to save alias: {}", e));
+        }
+    }
+
+    pub fn attachment_source(&self, local_path: &str) -> Option<SendTarget> {
+        self.conversations.iter().find_map(|conv| {
+            let msgs = conv.messages.as_ref()?;
+            attachments_of(msgs.iter())
+                .any(|att| att.local_path.as_deref() == Some(local_path))
+                .then(|| conv.send_target())
+                .flatten()
+        })
+    }
+
+    pub fn toggle_voice_recording(&mut self) {
+        match self.voice_recording.take() {
+            Some(recording) => self.finish_voice_recording(recording),
+            None => self.start_voice_recording(),
+        }
+    }
+
+    fn start_voice_recording(&mut self) {
+        let stamp = crate::util::now_millis();
+        for (recorder, args, ext) in VOICE_RECORDERS {
+            let path = std::env::temp_dir().join(format!("{}{}.{}", VOICE_NOTE_PREFIX, stamp, ext));
+            let spawned = Command::new(recorder)
+                .args(args)
+                .arg(&path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            if let Ok(child) = spawned {
+                self.voice_recording = Some(VoiceRecording {
+                    child,
+                    path,
+                    started: Instant::now(),
+                });
+                self.status_message = Some(format!("Recording with {}", recorder));
+                return;
+            }
+        }
+        self.status_message = Some("No recorder found (install arecord or ffmpeg)".to_string());
+    }
+
+    fn finish_voice_recording(&mut self, recording: VoiceRecording) {
+        unsafe {
+            libc::kill(recording.child.id() as libc::pid_t, libc::SIGINT);
+        }
+        self.status_message = Some("Finishing recording...".to_string());
+        self.finishing_recording = Some(recording);
+    }
+
+    pub fn attach_voice_recording(&mut self, path: PathBuf) {
+        if path.metadata().is_ok_and(|m| m.len() > 0) {
+            let path = self.keep_voice_note(path);
+            self.pending_attachments.push(path);
+            self.status_message = Some("Voice message attached".to_string());
+        } else {
+            let _ = std::fs::remove_file(&path);
+            self.status_message = Some("Recording failed".to_string());
+        }
+    }
+
+    fn is_voice_note(&self, path: &Path) -> bool {
+        let temp_dir = std::env::temp_dir();
+        let dirs = [Some(temp_dir.as_path()), self.voice_notes_dir.as_deref()];
+        dirs.into_iter().flatten().any(|dir| path.parent() == Some(dir))
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(VOICE_NOTE_PREFIX))
+    }
+
+    fn keep_voice_note(&self, path: PathBuf) -> PathBuf {
+        let (Some(dir), Some(name)) = (self.voice_notes_dir.as_ref(), path.file_name()) else {
+            return path;
+        };
+        let dest = dir.join(name);
+        let moved = std::fs::create_dir_all(dir).and_then(|_| {
+            std::fs::rename(&path, &dest)
+                .or_else(|_| std::fs::copy(&path, &dest).and_then(|_| std::fs::remove_file(&path)))
+        });
+        if moved.is_ok() { dest } else { path }
+    }
+
+    pub fn discard_pending_attachments(&mut self) {
+        for path in std::mem::take(&mut self.pending_attachments) {
+            if self.is_voice_note(&path) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    pub fn dismiss_view_once(&mut self) {
+        for (_, path) in self.view_once_copies.drain(..) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    pub fn cancel_voice_recording(&mut self) {
+        if let Some(recording) = self.voice_recording.take() {
+            let path = recording.path.clone();
+            drop(recording);
+            let _ = std::fs::remove_file(path);
+            self.status_message = Some("Recording discarded".to_string());
+        }
+    }
+
+    pub fn play_selected_audio(&mut self) {
+        let Some(path) = self
+            .selected_conversation()
+            .and_then(|c| c.get_selected_audio_paths().into_iter().next())
+            .and_then(|p| resolve_attachment_path(&p))
+        else {
+            self.status_message = Some("No downloaded audio in selection".to_string());
+            return;
+        };
+        let players: Vec<&str> = match &self.audio_player {
+            Some(player) => vec![player.as_str()],
+            None => AUDIO_PLAYERS.to_vec(),
+        };
+        for player in &players {
+            let mut command = Command::new(player);
+            match *player {
+                "mpv" => command.args(["--no-video", "--really-quiet"]),
+                "ffplay" => command.args(["-nodisp", "-autoexit", "-loglevel", "quiet"]),
+                _ => &mut command,
+            };
+            let spawned = command
+                .arg(&path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            if spawned.is_ok() {
+                self.status_message = Some(format!("Playing with {}", player));
+                return;
+            }
+        }
+        self.status_message = Some(format!("No audio player found (tried {})", players.join(", ")));
+    }
+
+    pub fn open_attachment_export(&mut self) {
+        if self.selected_conversation().is_none() {
+            return;
+        }
+        self.file_browser.purpose = FileBrowserPurpose::ExportAttachments;
+        self.file_browser.refresh();
+        self.focus = Focus::FileBrowser;
+    }
+
+    pub fn export_attachments(&mut self, dest: &Path) {
+        let Some(conv) = self.selected_conversation() else {
+            return;
+        };
+        let attachments = conv.collect_all_attachments(&self.storage);
+
+        let mut copied = 0;
+        let mut missing = 0;
+        let mut failed = 0;
+        for att in &attachments {
+            let Some(source) = att.local_path.as_deref().and_then(resolve_attachment_path) else {
+                missing += 1;
+                continue;
+            };
+            if !source.is_file() {
+                missing += 1;
+                continue;
+            }
+            let name = att
+                .filename
+                .clone()
+                .filter(|n| !n.is_empty())
+                .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_else(|| "attachment".to_string());
+            let name = Path::new(&name)
+                .file_name()
+                .map_or(name.clone(), |n| n.to_string_lossy().to_string());
+            match std::fs::copy(&source, unique_destination(dest, &name)) {
+                Ok(_) => copied += 1,
+                Err(_) => failed += 1,
+            }
+        }
+
+        let mut status = format!("Saved {} attachments to {}", copied, dest.display());
+        if missing > 0 {
+            status.push_str(&format!(", {} missing", missing));
+        }
+        if failed > 0 {
+            status.push_str(&format!(", {} failed", failed));
+        }
+        self.status_message = Some(status);
+        self.file_browser.purpose = FileBrowserPurpose::Attach;
+        self.focus = Focus::Messages;
+    }
+
+    pub fn typing_label(&self, conversation_id: &str) -> Option<String> {
+        let (sender, started) = self.typing.get(conversation_id)?;
+        if started.elapsed() >= TYPING_INDICATOR_TIMEOUT {
+            return None;
+        }
+        let conv = self
+            .conversations
+            .iter()
+            .find(|c| c.conversation.id == conversation_id)?;
+        Some(match conv.conversation.conversation_type {
+            ConversationType::Group => format!("{} is typing…", self.contact_name(sender)),
+            ConversationType::Direct => "typing…".to_string(),
+        })
+    }
+
+    pub fn next_typing_expiry(&self) -> Option<Instant> {
+        self.typing
+            .values()
+            .map(|(_, started)| *started + TYPING_INDICATOR_TIMEOUT)
+            .min()
+    }
 
-        if let Some(conv) = self.selected_conversation_mut() {
-            // Scroll up by fixed lines
-            conv.scroll_offset += SCROLL_LINES;
+    pub fn expire_typing(&mut self) -> bool {
+        let before = self.typing.len();
+        self.typing
+            .retain(|_, (_, started)| started.elapsed() < TYPING_INDICATOR_TIMEOUT);
+        self.typing.len() != before
+    }
 
-            // Check if we need to load more messages
-            if conv.has_more_messages {
-                let paths = conv.load_older_messages(&storage);
-                if !paths.is_empty() {
-                    self.pending_preload_paths.extend(paths);
-                }
+    fn handle_typing(
+        &mut self,
+        sender_uuid: &str,
+        source: Option<&str>,
+        typing: &crate::infrastructure::TypingMessage,
+    ) {
+        let conv = self.conversations.iter().find(|c| match &typing.group_id {
+            Some(group_id) => c.conversation.group_id.as_ref() == Some(group_id),
+            None => {
+                c.conversation.recipient_uuid.as_deref() == Some(sender_uuid)
+                    || (source.is_some() && c.conversation.recipient_number.as_deref() == source)
             }
+        });
+        let Some(conv_id) = conv.map(|c| c.conversation.id.clone()) else {
+            return;
+        };
+        if typing.action.as_deref() == Some("STARTED") {
+            self.typing
+                .insert(conv_id, (sender_uuid.to_string(), Instant::now()));
+        } else if self.typing.get(&conv_id).is_some_and(|(s, _)| s == sender_uuid) {
+            self.typing.remove(&conv_id);
         }
     }
 
-    pub fn scroll_messages_down(&mut self) {
-        if let Some(conv) = self.selected_conversation_mut() {
-            // Scroll down by fixed lines, but don't go below 0
-            conv.scroll_offset = conv.scroll_offset.saturating_sub(SCROLL_LINES);
+    pub fn next_flash_expiry(&self) -> Option<Instant> {
+        self.conversations
+            .iter()
+            .filter_map(|c| c.flash.as_ref())
+            .map(|(_, started)| *started + FLASH_DURATION)
+            .min()
+    }
+
+    pub fn expire_flash(&mut self) -> bool {
+        let mut expired = false;
+        for conv in &mut self.conversations {
+            if conv
+                .flash
+                .as_ref()
+                .is_some_and(|(_, started)| started.elapsed() >= FLASH_DURATION)
+            {
+                conv.flash = None;
+                expired = true;
+            }
         }
+        expired
     }
 
     pub fn cycle_focus(&mut self) {
+        if self.focus == Focus::ConversationFilter {
+            self.ensure_selection_matches_filter();
+        }
         self.focus = match self.focus {
-            Focus::Conversations | Focus::ConversationFilter => Focus::Messages,
+            Focus::Conversations => Focus::Messages,
+            Focus::ConversationFilter => Focus::Input,
             Focus::Messages => Focus::Input,
             Focus::Input | Focus::FileBrowser => Focus::Messages,
         };
@@ -752,34 +2790,159 @@ impl App {
         self.focus = Focus::Input;
     }
 
+    pub fn reply_to_selected(&mut self) {
+        let my_id = self.my_uuid.clone().or_else(|| self.my_number.clone());
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        let Some(msg) = conv.single_selected_message() else {
+            return;
+        };
+        let author_uuid = if msg.is_outgoing && msg.sender_uuid.is_empty() {
+            my_id.unwrap_or_default()
+        } else {
+            msg.sender_uuid.clone()
+        };
+        let attachment_name = match &msg.content {
+            MessageContent::Attachment { attachments } => attachments
+                .first()
+                .map(|a| a.filename.clone().unwrap_or_else(|| "attachment".to_string())),
+            _ => None,
+        };
+        let text = if attachment_name.is_some() {
+            None
+        } else {
+            conv.get_selected_text()
+        };
+        let quote = Quote {
+            author_uuid,
+            timestamp: msg.timestamp,
+            text,
+            attachment_name,
+        };
+        let conversation_id = conv.conversation.id.clone();
+        conv.exit_selection_mode();
+        self.pending_reply = Some(PendingReply { conversation_id, quote });
+        self.focus = Focus::Input;
+    }
+
+    pub fn active_reply(&self) -> Option<&Quote> {
+        let conv_id = &self.selected_conversation()?.conversation.id;
+        self.pending_reply
+            .as_ref()
+            .filter(|r| r.conversation_id == *conv_id && self.broadcast_targets.is_empty())
+            .map(|r| &r.quote)
+    }
+
+    pub fn take_reply(&mut self) -> Option<Quote> {
+        let quote = self.active_reply().cloned();
+        self.pending_reply = None;
+        quote
+    }
+
     pub fn queue_send_message(&mut self, text: String) {
-        if (!text.is_empty() || !self.pending_attachments.is_empty())
-            && self.selected_conversation().is_some()
-        {
-            self.pending_send = Some(text);
+        self.stop_typing();
+        if text.is_empty() && self.pending_attachments.is_empty() {
+            return;
+        }
+        if !self.broadcast_targets.is_empty() {
+            self.pending_broadcast = Some(text);
+        } else if let Some(conv) = self.selected_conversation() {
+            let untrusted = self
+                .untrusted_identity(&conv.conversation)
+                .filter(|_| self.verify_before_send)
+                .and_then(|identity| identity.uuid.clone().or_else(|| identity.number.clone()));
+            match untrusted {
+                Some(identifier) => {
+                    let name = conv.conversation.display_name();
+                    self.input.insert_str(&text);
+                    self.confirm(
+                        format!("Safety number with {} has changed. Trust it and send? (y/n)", name),
+                        move |app| {
+                            app.input.clear();
+                            app.pending_trust = Some(identifier);
+                            app.pending_send = Some(text);
+                        },
+                    );
+                }
+                None => self.pending_send = Some(text),
+            }
+        }
+    }
+
+    pub fn update_typing(&mut self) {
+        if self.input.text.is_empty() || !self.broadcast_targets.is_empty() {
+            self.stop_typing();
+            return;
+        }
+        let target = self.get_send_target();
+        if target != self.typing_target {
+            self.stop_typing();
+        }
+        let Some(target) = target else {
+            return;
+        };
+        let stale = self
+            .typing_sent_at
+            .is_none_or(|sent| sent.elapsed() >= TYPING_REFRESH_INTERVAL);
+        if stale {
+            self.pending_typing.push((target.clone(), true));
+            self.typing_target = Some(target);
+            self.typing_sent_at = Some(Instant::now());
+        }
+    }
+
+    pub fn stop_typing(&mut self) {
+        self.typing_sent_at = None;
+        if let Some(target) = self.typing_target.take() {
+            self.pending_typing.push((target, false));
         }
     }
 
     pub fn get_send_target(&self) -> Option<SendTarget> {
-        let conv = self.selected_conversation()?;
-        match conv.conversation.conversation_type {
-            ConversationType::Direct => {
-                let recipient = conv
-                    .conversation
-                    .recipient_uuid
-                    .clone()
-                    .or_else(|| conv.conversation.recipient_number.clone())?;
-                Some(SendTarget::Direct(recipient))
-            }
-            ConversationType::Group => {
-                let group_id = conv.conversation.group_id.clone()?;
-                Some(SendTarget::Group(group_id))
-            }
+        self.selected_conversation()?.send_target()
+    }
+
+    pub fn toggle_broadcast_target(&mut self) {
+        let Some(id) = self.selected_conversation().map(|c| c.conversation.id.clone()) else {
+            return;
+        };
+        if let Some(pos) = self.broadcast_targets.iter().position(|t| *t == id) {
+            self.broadcast_targets.remove(pos);
+        } else {
+            self.broadcast_targets.push(id);
+        }
+    }
+
+    pub fn mark_selected_unread(&mut self) {
+        let storage = self.storage.clone();
+        let Some(conv) = self.selected_conversation_mut() else {
+            return;
+        };
+        if let Err(e) = storage.mark_conversation_unread(&conv.conversation.id) {
+            self.status_message = Some(format!("Failed to mark unread: {}", e));
+            return;
+        }
+        conv.conversation.unread_count = conv.conversation.unread_count.max(1);
+        if let Some(ref mut msgs) = conv.messages
+            && let Some(newest) = msgs.iter_mut().rev().find(|m| !m.is_outgoing)
+        {
+            newest.is_read = false;
+        }
+        if let Ok(Some(stored)) = storage.get_conversation(&conv.conversation.id) {
+            conv.conversation.marked_unread_timestamp = stored.marked_unread_timestamp;
         }
     }
 
     pub fn mark_current_conversation_read(&mut self) -> Option<(String, Vec<i64>)> {
+        if self.read_marking_deferred {
+            return None;
+        }
         let conv = self.selected_conversation()?;
+        let marked_unread = conv.conversation.marked_unread_timestamp;
+        if marked_unread.is_some() && self.focus == Focus::Conversations {
+            return None;
+        }
         if conv.conversation.conversation_type != ConversationType::Direct {
             return None;
         }
@@ -801,31 +2964,64 @@ impl App {
             .storage
             .mark_messages_read(&conversation_id, max_timestamp);
 
-        if let Some(conv) = self.selected_conversation_mut()
-            && let Some(ref mut msgs) = conv.messages
-        {
-            for msg in msgs.iter_mut() {
-                if !msg.is_outgoing && timestamps.contains(&msg.timestamp) {
-                    msg.is_read = true;
+        let storage = self.storage.clone();
+        if let Some(conv) = self.selected_conversation_mut() {
+            if let Some(ref mut msgs) = conv.messages {
+                for msg in msgs.iter_mut() {
+                    if !msg.is_outgoing && timestamps.contains(&msg.timestamp) {
+                        msg.is_read = true;
+                    }
                 }
             }
+            if marked_unread.is_some() {
+                conv.conversation.marked_unread_timestamp = None;
+                let _ = storage.update_conversation(&conv.conversation);
+            }
         }
 
-        Some((recipient, timestamps))
+        let timestamps: Vec<i64> = timestamps
+            .into_iter()
+            .filter(|ts| Some(*ts) != marked_unread)
+            .collect();
+        if timestamps.is_empty() {
+            return None;
+        }
+        self.send_read_receipts.then_some((recipient, timestamps))
     }
 
     pub fn handle_incoming_message(&mut self, msg: IncomingMessage) {
+        self.last_notification = Instant::now();
         let envelope = &msg.envelope;
-        let sender_uuid = match envelope.source_uuid.as_ref() {
-            Some(uuid) => uuid,
-            None => return,
+        let Some(sender_uuid) = envelope.source_uuid.as_ref().or(envelope.source.as_ref()) else {
+            return;
         };
         let sender_name = envelope.source_name.clone();
         let timestamp = envelope.timestamp.unwrap_or_else(now_millis);
 
+        if let Some(receipt) = &envelope.receipt_message {
+            self.handle_receipt(sender_uuid, receipt);
+        }
+
+        if let Some(typing) = &envelope.typing_message {
+            self.handle_typing(sender_uuid, envelope.source.as_deref(), typing);
+        }
+
         if let Some(data) = &envelope.data_message {
+            if let Some(reaction) = &data.reaction {
+                self.apply_reaction(sender_uuid, reaction, timestamp);
+                return;
+            }
+            if let Some(delete) = &data.remote_delete {
+                self.handle_remote_delete(sender_uuid, delete.timestamp);
+                return;
+            }
+
             let text = data.message.clone().unwrap_or_default();
-            if text.is_empty() && data.attachments.is_empty() {
+            let has_content = !text.is_empty()
+                || !data.attachments.is_empty()
+                || data.sticker.is_some()
+                || data.quote.is_some();
+            if !has_content && data.expires_in_seconds.is_none() {
                 return;
             }
 
@@ -851,6 +3047,13 @@ impl App {
             };
 
             if let Some(conv) = conversation {
+                if let Some(seconds) = data.expires_in_seconds {
+                    self.apply_expiration(&conv, seconds.max(0) as u32);
+                }
+                if !has_content {
+                    return;
+                }
+
                 let content = if !data.attachments.is_empty() {
                     let attachments = data
                         .attachments
@@ -861,29 +3064,43 @@ impl App {
                             filename: a.filename.clone(),
                             size: a.size.map(|s| s as u64),
                             local_path: a.id.clone(),
+                            view_once: data.view_once,
+                            viewed: false,
                         })
                         .collect();
                     MessageContent::Attachment { attachments }
+                } else if let Some(sticker) = &data.sticker {
+                    MessageContent::Sticker {
+                        pack_id: sticker.pack_id.clone(),
+                        sticker_id: sticker.sticker_id,
+                    }
                 } else {
                     MessageContent::Text { body: text }
                 };
 
+                let quote = data.quote.as_ref().and_then(convert_quote);
+
                 let message = Message {
                     id: uuid::Uuid::new_v4().to_string(),
                     conversation_id: conv.id.clone(),
                     sender_uuid: sender_uuid.clone(),
                     sender_name: sender_name.clone(),
                     timestamp,
-                    server_timestamp: None,
+                    server_timestamp: envelope.server_received_timestamp,
                     received_at: now_millis(),
                     content,
-                    quote: None,
+                    quote,
                     is_outgoing,
                     is_read: is_outgoing,
                     is_deleted: false,
                     is_edited: false,
+                    link_preview: data.previews.first().map(convert_preview),
                 };
 
+                if self.typing.get(&conv.id).is_some_and(|(s, _)| s == sender_uuid) {
+                    self.typing.remove(&conv.id);
+                }
+                self.queue_notification(&conv, &message);
                 let _ = self.storage.save_message(&message);
                 self.add_message_to_conversation(&conv.id, message);
             }
@@ -901,8 +3118,21 @@ impl App {
                 return;
             }
 
+            if let Some(reaction) = &sent.reaction {
+                self.apply_reaction(sender_uuid, reaction, sent.timestamp.unwrap_or(timestamp));
+                return;
+            }
+            if let Some(delete) = &sent.remote_delete {
+                self.handle_remote_delete(sender_uuid, delete.timestamp);
+                return;
+            }
+
             let text = sent.message.clone().unwrap_or_default();
-            if text.is_empty() && sent.attachments.is_empty() {
+            let has_content = !text.is_empty()
+                || !sent.attachments.is_empty()
+                || sent.sticker.is_some()
+                || sent.quote.is_some();
+            if !has_content && sent.expires_in_seconds.is_none() {
                 return;
             }
 
@@ -936,6 +3166,13 @@ impl App {
             };
 
             if let Some(conv) = conversation {
+                if let Some(seconds) = sent.expires_in_seconds {
+                    self.apply_expiration(&conv, seconds.max(0) as u32);
+                }
+                if !has_content {
+                    return;
+                }
+
                 let content = if !sent.attachments.is_empty() {
                     let attachments = sent
                         .attachments
@@ -946,9 +3183,16 @@ impl App {
                             filename: a.filename.clone(),
                             size: a.size.map(|s| s as u64),
                             local_path: a.id.clone(),
+                            view_once: sent.view_once,
+                            viewed: false,
                         })
                         .collect();
                     MessageContent::Attachment { attachments }
+                } else if let Some(sticker) = &sent.sticker {
+                    MessageContent::Sticker {
+                        pack_id: sticker.pack_id.clone(),
+                        sticker_id: sticker.sticker_id,
+                    }
                 } else {
                     MessageContent::Text { body: text }
                 };
@@ -959,14 +3203,15 @@ impl App {
                     sender_uuid: sender_uuid.clone(),
                     sender_name: sender_name.clone(),
                     timestamp: sent.timestamp.unwrap_or(timestamp),
-                    server_timestamp: None,
+                    server_timestamp: envelope.server_received_timestamp,
                     received_at: now_millis(),
                     content,
-                    quote: None,
+                    quote: sent.quote.as_ref().and_then(convert_quote),
                     is_outgoing: true,
                     is_read: true,
                     is_deleted: false,
                     is_edited: false,
+                    link_preview: sent.previews.first().map(convert_preview),
                 };
 
                 let _ = self.storage.save_message(&message);
@@ -975,13 +3220,217 @@ impl App {
         }
     }
 
+    fn resolve_signal_id(&self, authors: &[&str], timestamp: i64) -> Option<Message> {
+        let matches = |sender: &str| sender.is_empty() || authors.contains(&sender);
+        let loaded = self
+            .conversations
+            .iter()
+            .filter_map(|c| c.messages.as_ref())
+            .flatten()
+            .find(|m| m.timestamp == timestamp && matches(&m.sender_uuid));
+        if let Some(msg) = loaded {
+            return Some(msg.clone());
+        }
+        authors.iter().copied().chain([""]).find_map(|author| {
+            self.storage
+                .get_message_by_signal_id(author, timestamp)
+                .ok()
+                .flatten()
+        })
+    }
+
+    fn loaded_message_mut(&mut self, target: &Message) -> Option<&mut Message> {
+        self.conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == target.conversation_id)?
+            .messages
+            .as_mut()?
+            .iter_mut()
+            .find(|m| m.id == target.id)
+    }
+
+    pub fn notifications_silenced(&self) -> bool {
+        self.do_not_disturb || self.snooze_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    pub fn silence_label(&self) -> Option<String> {
+        if self.do_not_disturb {
+            return Some("🔕 Do not disturb".to_string());
+        }
+        let remaining = self.snooze_until?.checked_duration_since(Instant::now())?;
+        let seconds = remaining.as_secs().max(1) as u32;
+        let unit = relative_unit(seconds);
+        Some(format!("🔕 Snoozed for {}", format_duration(seconds.div_ceil(unit) * unit)))
+    }
+
+    pub fn sync_stale_label(&self) -> Option<String> {
+        let quiet = self.last_notification.elapsed();
+        let seconds = quiet.as_secs() as u32;
+        (quiet >= SYNC_STALE_THRESHOLD)
+            .then(|| format!("last sync {} ago", format_duration(seconds - seconds % relative_unit(seconds))))
+    }
+
+    pub fn expire_snooze(&mut self) -> bool {
+        if self.snooze_until.is_some_and(|until| Instant::now() >= until) {
+            self.snooze_until = None;
+            return true;
+        }
+        false
+    }
+
+    pub fn start_snooze_edit(&mut self) {
+        self.prompt_text(
+            "Snooze notifications (e.g. 30m, 1h, 1d; empty toggles do not disturb)",
+            String::new(),
+            |app, text| match parse_duration(&text) {
+                Some(0) => {
+                    let silenced = app.notifications_silenced();
+                    app.do_not_disturb = !silenced;
+                    app.snooze_until = None;
+                    app.status_message = Some(if silenced {
+                        "Notifications on".to_string()
+                    } else {
+                        "Do not disturb on".to_string()
+                    });
+                }
+                Some(seconds) => {
+                    app.do_not_disturb = false;
+                    app.snooze_until = Some(Instant::now() + Duration::from_secs(seconds as u64));
+                    app.status_message = Some(format!("Notifications snoozed for {}", format_duration(seconds)));
+                }
+                None => app.status_message = Some(format!("Invalid duration: {}", text.trim())),
+            },
+        );
+    }
+
+    fn queue_notification(&mut self, conv: &Conversation, message: &Message) {
+        if message.is_outgoing || conv.is_muted || self.notifications_silenced() {
+            return;
+        }
+        let viewing = self
+            .selected_conversation()
+            .is_some_and(|c| c.conversation.id == conv.id);
+        if viewing && self.terminal_focused {
+            return;
+        }
+        if self.bell_enabled {
+            self.pending_bell = true;
+        }
+        if !self.notifications_enabled {
+            return;
+        }
+        let sender = message
+            .sender_name
+            .clone()
+            .unwrap_or_else(|| self.contact_name(&message.sender_uuid));
+        let sender = match conv.conversation_type {
+            ConversationType::Group => format!("{} in {}", sender, conv.display_name()),
+            ConversationType::Direct => sender,
+        };
+        let notification = match self.notification_privacy {
+            NotificationPrivacy::Full => {
+                let body = match &message.content {
+                    MessageContent::Text { body } => crate::util::mask_spoilers(body),
+                    MessageContent::Attachment { attachments } if attachments.iter().any(|a| a.view_once) => {
+                        "View-once media".to_string()
+                    }
+                    MessageContent::Attachment { attachments } => format!("📎 {} attachment(s)", attachments.len()),
+                    MessageContent::Sticker { .. } => "Sticker".to_string(),
+                    MessageContent::RemoteDeleted => return,
+                };
+                (sender, truncate_to_width(&body, self.preview_length))
+            }
+            NotificationPrivacy::SenderOnly => (sender, "New message".to_string()),
+            NotificationPrivacy::Generic => ("signal-tty".to_string(), "New message".to_string()),
+        };
+        self.pending_notifications.push(notification);
+    }
+
+    fn apply_reaction(
+        &mut self,
+        sender_uuid: &str,
+        reaction: &crate::infrastructure::Reaction,
+        timestamp: i64,
+    ) {
+        let Some(target_timestamp) = reaction.target_sent_timestamp else {
+            return;
+        };
+        let authors: Vec<&str> = [
+            reaction.target_author_uuid.as_deref(),
+            reaction.target_author.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let Some(target) = self.resolve_signal_id(&authors, target_timestamp) else {
+            return;
+        };
+
+        let conv = self
+            .conversations
+            .iter_mut()
+            .find(|c| c.conversation.id == target.conversation_id);
+
+        if reaction.is_remove {
+            let _ = self
+                .storage
+                .remove_reaction(&target.id, sender_uuid, &reaction.emoji);
+            if let Some(reactions) = conv.and_then(|c| c.reactions.get_mut(&target.id)) {
+                reactions.retain(|r| !(r.sender_uuid == sender_uuid && r.emoji == reaction.emoji));
+            }
+        } else {
+            let new_reaction = Reaction {
+                id: uuid::Uuid::new_v4().to_string(),
+                message_id: target.id.clone(),
+                sender_uuid: sender_uuid.to_string(),
+                emoji: reaction.emoji.clone(),
+                timestamp,
+            };
+            let _ = self.storage.save_reaction(&new_reaction);
+            if let Some(conv) = conv {
+                let reactions = conv.reactions.entry(target.id).or_default();
+                reactions.retain(|r| !(r.sender_uuid == sender_uuid && r.emoji == reaction.emoji));
+                reactions.push(new_reaction);
+            }
+        }
+    }
+
+    fn handle_receipt(&mut self, sender_uuid: &str, receipt: &crate::infrastructure::ReceiptMessage) {
+        let state = match receipt.receipt_type.as_deref() {
+            Some("DELIVERY") => DeliveryState::Delivered,
+            Some("READ") | Some("VIEWED") => DeliveryState::Read,
+            _ => return,
+        };
+        let me = self.my_uuid.clone().unwrap_or_default();
+        for &timestamp in &receipt.timestamps {
+            let Some(target) = self.resolve_signal_id(&[&me], timestamp) else {
+                continue;
+            };
+            if !target.is_outgoing {
+                continue;
+            }
+            self.record_delivery(&target, sender_uuid, state);
+        }
+    }
+
+    fn handle_remote_delete(&mut self, sender_uuid: &str, target_timestamp: i64) {
+        let Some(target) = self.resolve_signal_id(&[sender_uuid], target_timestamp) else {
+            return;
+        };
+        let _ = self
+            .storage
+            .mark_message_deleted(&target.sender_uuid, target.timestamp);
+        if let Some(msg) = self.loaded_message_mut(&target) {
+            msg.content = MessageContent::RemoteDeleted;
+            msg.is_deleted = true;
+        }
+    }
+
     fn handle_edit_message(
         &mut self,
         sender_uuid: &str,
         edit: &crate::infrastructure::EditMessage,
     ) {
-        let target_timestamp = edit.target_sent_timestamp;
-
         let Some(data) = &edit.data_message else {
             return;
         };
@@ -989,27 +3438,17 @@ impl App {
         if new_text.is_empty() {
             return;
         }
+        let Some(target) = self.resolve_signal_id(&[sender_uuid], edit.target_sent_timestamp) else {
+            return;
+        };
 
         let new_content = MessageContent::Text { body: new_text };
         let _ = self
             .storage
-            .update_message_content(sender_uuid, target_timestamp, &new_content);
-        let _ = self
-            .storage
-            .update_message_content("", target_timestamp, &new_content);
-
-        for conv in &mut self.conversations {
-            if let Some(ref mut msgs) = conv.messages {
-                for msg in msgs.iter_mut() {
-                    if msg.timestamp == target_timestamp
-                        && (msg.sender_uuid == sender_uuid || msg.sender_uuid.is_empty())
-                    {
-                        msg.content = new_content.clone();
-                        msg.is_edited = true;
-                        return;
-                    }
-                }
-            }
+            .update_message_content(&target.sender_uuid, target.timestamp, &new_content);
+        if let Some(msg) = self.loaded_message_mut(&target) {
+            msg.content = new_content;
+            msg.is_edited = true;
         }
     }
 
@@ -1022,9 +3461,6 @@ impl App {
         if let Some(idx) = found_idx {
             let timestamp = message.timestamp;
             let conv_view = &mut self.conversations[idx];
-            if conv_view.messages.is_none() {
-                conv_view.load_messages(&self.storage);
-            }
             conv_view.add_message(message);
             conv_view.conversation.last_message_timestamp = Some(timestamp);
 
@@ -1032,7 +3468,21 @@ impl App {
             return;
         }
 
-        self.load_conversations();
+        self.insert_conversation(conversation_id);
+    }
+
+    fn insert_conversation(&mut self, conversation_id: &str) {
+        let Ok(Some(conversation)) = self.storage.get_conversation(conversation_id) else {
+            return;
+        };
+        let idx = self.conversations.partition_point(|c| {
+            c.conversation.last_message_timestamp >= conversation.last_message_timestamp
+        });
+        if idx <= self.selected && !self.conversations.is_empty() {
+            self.selected += 1;
+        }
+        self.conversations
+            .insert(idx, ConversationView::new(conversation, &self.storage));
     }
 
     fn sort_conversations(&mut self) {
@@ -1065,8 +3515,9 @@ impl App {
                 .iter()
                 .enumerate()
                 .filter(|(_, c)| {
-                    self.show_empty_conversations
-                        || c.conversation.last_message_timestamp.is_some()
+                    (self.show_archived || !c.conversation.is_archived)
+                        && (self.show_empty_conversations
+                            || c.conversation.last_message_timestamp.is_some())
                 })
                 .map(|(i, _)| i)
                 .collect();
@@ -1128,9 +3579,7 @@ impl App {
 
         if let Some(&new_idx) = indices.get(new_pos) {
             self.selected = new_idx;
-            if self.conversations[self.selected].load_messages(&self.storage) {
-                self.needs_image_preload = true;
-            }
+            self.load_selected_messages();
         }
     }
 
@@ -1141,16 +3590,211 @@ impl App {
         }
         if let Some(&first) = indices.first() {
             self.selected = first;
-            if self.conversations[self.selected].load_messages(&self.storage) {
-                self.needs_image_preload = true;
-            }
+            self.load_selected_messages();
         }
     }
 }
 
-fn now_millis() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(conversation_id: &str, id: &str, timestamp: i64) -> Message {
+        Message {
+            id: id.to_string(),
+            conversation_id: conversation_id.to_string(),
+            sender_uuid: "sender".to_string(),
+            sender_name: None,
+            timestamp,
+            server_timestamp: None,
+            received_at: timestamp,
+            content: MessageContent::Text { body: id.to_string() },
+            quote: None,
+            is_outgoing: false,
+            is_read: true,
+            is_deleted: false,
+            is_edited: false,
+            link_preview: None,
+        }
+    }
+
+    fn storage_with_messages(count: i64) -> (SqliteStorage, Conversation) {
+        let storage = SqliteStorage::open(":memory:", None).unwrap();
+        let conv = storage.get_or_create_direct_conversation("uuid", None, None).unwrap();
+        for i in 0..count {
+            storage.save_message(&message(&conv.id, &format!("m{i}"), 1_000 + i)).unwrap();
+        }
+        (storage, conv)
+    }
+
+    #[test]
+    fn loading_older_messages_keeps_selection_on_the_same_messages() {
+        let (storage, conv) = storage_with_messages(150);
+        let mut view = ConversationView::new(conv, &storage);
+        view.load_messages(&storage);
+        view.selection = Some(MessageSelection { anchor: 10, cursor: 12 });
+        view.visible_range = Some((5, 20));
+        let id_at = |view: &ConversationView, idx: usize| view.messages.as_ref().unwrap()[idx].id.clone();
+        let (anchor, cursor) = (id_at(&view, 10), id_at(&view, 12));
+        let (start, end) = (id_at(&view, 5), id_at(&view, 20));
+
+        view.load_older_messages(&storage);
+
+        assert_eq!(view.messages.as_ref().unwrap().len(), 150);
+        let selection = view.selection.as_ref().unwrap();
+        assert_eq!(id_at(&view, selection.anchor), anchor);
+        assert_eq!(id_at(&view, selection.cursor), cursor);
+        let (new_start, new_end) = view.visible_range.unwrap();
+        assert_eq!(id_at(&view, new_start), start);
+        assert_eq!(id_at(&view, new_end), end);
+    }
+
+    fn app_with(storage: SqliteStorage) -> App {
+        let signal = SignalClient::new(None, crate::infrastructure::RpcTimeouts::default(), 1024);
+        App::new(Arc::new(storage), Arc::new(signal), None)
+    }
+
+    #[test]
+    fn send_result_lands_in_its_conversation_after_switching_away() {
+        let (storage, sent_to) = storage_with_messages(3);
+        let other = storage.get_or_create_direct_conversation("other", None, None).unwrap();
+        let mut app = app_with(storage);
+        app.load_conversations();
+        let position = |app: &App, id: &str| app.conversations.iter().position(|c| c.conversation.id == id).unwrap();
+
+        app.selected = position(&app, &sent_to.id);
+        app.load_selected_messages();
+        let optimistic = message(&sent_to.id, "optimistic", 5_000);
+        app.add_message_to_conversation(&sent_to.id, optimistic.clone());
+        app.selected = position(&app, &other.id);
+        app.load_selected_messages();
+
+        let confirmed = Message { timestamp: 6_000, ..optimistic };
+        app.reconcile_sent_message(&confirmed);
+
+        let view = &app.conversations[position(&app, &sent_to.id)];
+        let loaded = view.messages.as_ref().unwrap();
+        assert_eq!(loaded.iter().filter(|m| m.id == "optimistic").count(), 1);
+        assert_eq!(loaded.iter().find(|m| m.id == "optimistic").unwrap().timestamp, 6_000);
+        assert_eq!(app.storage.get_message("optimistic").unwrap().unwrap().timestamp, 6_000);
+        assert_eq!(app.conversations[app.selected].conversation.id, other.id);
+    }
+
+    #[test]
+    fn eviction_keeps_conversations_with_sends_in_flight_queued() {
+        let storage = SqliteStorage::open(":memory:", None).unwrap();
+        let ids: Vec<String> = (0..MAX_LOADED_CONVERSATIONS + 3)
+            .map(|i| {
+                let conv = storage.get_or_create_direct_conversation(&format!("uuid{i}"), None, None).unwrap();
+                storage.save_message(&message(&conv.id, &format!("m{i}"), 1_000 + i as i64)).unwrap();
+                conv.id
+            })
+            .collect();
+        let mut app = app_with(storage);
+        app.load_conversations();
+        let open = |app: &mut App, id: &str| {
+            app.selected = app.conversations.iter().position(|c| c.conversation.id == id).unwrap();
+            app.load_selected_messages();
+        };
+        let loaded = |app: &App, id: &str| {
+            app.conversations.iter().any(|c| c.conversation.id == id && c.messages.is_some())
+        };
+
+        open(&mut app, &ids[0]);
+        app.conversations[app.selected].sending.insert("m0".to_string());
+        for id in &ids[1..] {
+            open(&mut app, id);
+        }
+        assert!(loaded(&app, &ids[0]));
+        assert!(app.recent_conversations.contains(&ids[0]));
+        assert!(!loaded(&app, &ids[1]));
+        assert_eq!(app.recent_conversations.len(), MAX_LOADED_CONVERSATIONS);
+
+        app.conversations.iter_mut().find(|c| c.conversation.id == ids[0]).unwrap().sending.clear();
+        open(&mut app, &ids[1]);
+        assert!(!loaded(&app, &ids[0]));
+        assert!(!app.recent_conversations.contains(&ids[0]));
+    }
+
+    #[test]
+    fn switching_account_drops_the_previous_accounts_state() {
+        let (storage, conv) = storage_with_messages(1);
+        let mut app = app_with(storage);
+        app.load_conversations();
+        app.pending_attachments.push(PathBuf::from("/tmp/photo.jpg"));
+        app.pending_number_lookup = Some("+15550100000".to_string());
+        app.pending_group_fetch = Some("group".to_string());
+        app.pending_notifications.push(("title".to_string(), "body".to_string()));
+        app.typing.insert(conv.id.clone(), ("sender".to_string(), Instant::now()));
+        app.device_link = Some("sgnl://linkdevice".to_string());
+
+        let storage = Arc::new(SqliteStorage::open(":memory:", None).unwrap());
+        let signal = Arc::new(SignalClient::new(None, crate::infrastructure::RpcTimeouts::default(), 1024));
+        app.switch_account(storage, PathBuf::from("/nonexistent/state"), signal, "+15550100001".to_string());
+
+        assert!(app.pending_attachments.is_empty());
+        assert!(app.pending_number_lookup.is_none());
+        assert!(app.pending_group_fetch.is_none());
+        assert!(app.pending_notifications.is_empty());
+        assert!(app.typing.is_empty());
+        assert!(app.device_link.is_none());
+        assert!(app.conversations.is_empty());
+    }
+
+    #[test]
+    fn direct_send_target_prefers_a_valid_uuid() {
+        let (storage, _) = storage_with_messages(0);
+        let uuid = "a1b2c3d4-0000-4000-8000-00000000000f".to_string();
+        let target = |recipient_uuid: &str, number: Option<&str>| {
+            let conv = Conversation::new_direct(recipient_uuid.to_string(), number.map(String::from), None);
+            ConversationView::new(conv, &storage).send_target()
+        };
+
+        assert_eq!(target(&uuid, Some("+15550100000")), Some(SendTarget::Direct(uuid.clone())));
+        assert_eq!(target("+15550100000", Some("+15550100000")), Some(SendTarget::Direct("+15550100000".to_string())));
+        assert_eq!(target("legacy-id", None), Some(SendTarget::Direct("legacy-id".to_string())));
+    }
+
+    #[test]
+    fn paging_moves_by_exactly_the_visible_height() {
+        for height in [0, 1, 7, 24, 80] {
+            let (storage, conv) = storage_with_messages(20);
+            let mut app = app_with(storage);
+            app.load_conversations();
+            app.selected = app.conversations.iter().position(|c| c.conversation.id == conv.id).unwrap();
+            app.load_selected_messages();
+            app.messages_height = height;
+            let step = height.max(1);
+
+            app.page_messages_up();
+            app.page_messages_up();
+            assert_eq!(app.conversations[app.selected].scroll_offset, step * 2);
+            app.page_messages_down();
+            assert_eq!(app.conversations[app.selected].scroll_offset, step);
+            app.page_messages_down();
+            app.page_messages_down();
+            assert_eq!(app.conversations[app.selected].scroll_offset, 0);
+        }
+    }
+
+    #[test]
+    fn home_loads_at_most_one_older_page_per_press() {
+        let (storage, conv) = storage_with_messages(250);
+        let mut app = app_with(storage);
+        app.load_conversations();
+        app.selected = app.conversations.iter().position(|c| c.conversation.id == conv.id).unwrap();
+        app.load_selected_messages();
+        let loaded = |app: &App| app.conversations[app.selected].messages.as_ref().unwrap().len();
+        assert_eq!(loaded(&app), 100);
+
+        app.conversations[app.selected].visible_range = Some((40, 60));
+        app.scroll_messages_to_top();
+        assert_eq!(loaded(&app), 100);
+        assert_eq!(app.conversations[app.selected].scroll_offset, usize::MAX);
+
+        app.conversations[app.selected].visible_range = Some((0, 20));
+        app.scroll_messages_to_top();
+        assert_eq!(loaded(&app), 200);
+        assert_eq!(app.conversations[app.selected].scroll_offset, usize::MAX);
+    }
 }
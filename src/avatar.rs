@@ -2,11 +2,20 @@ use ratatui_image::picker::Picker;
 use ratatui_image::protocol::StatefulProtocol;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+struct CachedAvatar {
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+    protocol: Option<StatefulProtocol>,
+}
 
 pub struct AvatarManager {
     picker: Picker,
     avatars_dir: PathBuf,
-    cache: HashMap<String, Option<StatefulProtocol>>,
+    cache: HashMap<String, CachedAvatar>,
 }
 
 impl AvatarManager {
@@ -21,27 +30,61 @@ impl AvatarManager {
         })
     }
 
+    pub fn set_picker(&mut self, picker: Picker) {
+        self.picker = picker;
+        self.cache.clear();
+    }
+
+    pub fn refresh(&mut self) {
+        self.cache.clear();
+    }
+
     pub fn get_avatar(&mut self, identifier: &str) -> Option<&mut StatefulProtocol> {
-        if !self.cache.contains_key(identifier) {
-            let protocol = self.load_avatar(identifier);
-            self.cache.insert(identifier.to_string(), protocol);
+        let needs_check = self
+            .cache
+            .get(identifier)
+            .is_none_or(|cached| cached.checked_at.elapsed() >= RECHECK_INTERVAL);
+
+        if needs_check {
+            let modified = self
+                .avatar_path(identifier)
+                .and_then(|path| path.metadata().ok()?.modified().ok());
+            match self.cache.get_mut(identifier) {
+                Some(cached) if cached.modified == modified => cached.checked_at = Instant::now(),
+                _ => {
+                    let protocol = self.load_avatar(identifier);
+                    self.cache.insert(
+                        identifier.to_string(),
+                        CachedAvatar {
+                            modified,
+                            checked_at: Instant::now(),
+                            protocol,
+                        },
+                    );
+                }
+            }
         }
 
-        self.cache.get_mut(identifier).and_then(|opt| opt.as_mut())
+        self.cache
+            .get_mut(identifier)
+            .and_then(|cached| cached.protocol.as_mut())
     }
 
-    fn load_avatar(&mut self, identifier: &str) -> Option<StatefulProtocol> {
+    fn avatar_path(&self, identifier: &str) -> Option<PathBuf> {
         let profile_path = self.avatars_dir.join(format!("profile-{}", identifier));
         let contact_path = self.avatars_dir.join(format!("contact-{}", identifier));
 
-        let avatar_path = if profile_path.exists() {
-            profile_path
+        if profile_path.exists() {
+            Some(profile_path)
         } else if contact_path.exists() {
-            contact_path
+            Some(contact_path)
         } else {
-            return None;
-        };
+            None
+        }
+    }
 
+    fn load_avatar(&mut self, identifier: &str) -> Option<StatefulProtocol> {
+        let avatar_path = self.avatar_path(identifier)?;
         let data = std::fs::read(&avatar_path).ok()?;
         image::load_from_memory(&data)
             .ok()
@@ -69,11 +112,8 @@ impl AvatarManager {
     }
 
     fn has_avatar_file(&self, identifier: &str) -> bool {
-        let profile = self.avatars_dir.join(format!("profile-{}", identifier));
-        let contact = self.avatars_dir.join(format!("contact-{}", identifier));
-        profile.exists() || contact.exists()
+        self.avatar_path(identifier).is_some()
     }
-
 }
 
 fn dirs_avatar_path() -> Option<PathBuf> {
@@ -1,10 +1,26 @@
-use crate::app::{App, Focus, PendingRemoteDelete};
+use crate::app::{App, FileBrowserPurpose, Focus, resolve_attachment_path};
 use crate::storage::StorageRepository;
+use base64::prelude::*;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Stdio};
 
-fn copy_to_clipboard(text: &str) {
+const OSC52_MAX_ENCODED_BYTES: usize = 100_000;
+
+fn copy_to_clipboard(app: &mut App, text: &str) {
+    if app.osc52_clipboard {
+        let encoded = BASE64_STANDARD.encode(text);
+        if encoded.len() > OSC52_MAX_ENCODED_BYTES {
+            app.status_message = Some("Selection too large to copy over OSC 52".to_string());
+            return;
+        }
+        let mut out = std::io::stdout();
+        let _ = write!(out, "\x1b]52;c;{}\x07", encoded);
+        let _ = out.flush();
+        return;
+    }
+
     if let Ok(mut child) = Command::new("wl-copy")
         .stdin(Stdio::piped())
         .stdout(Stdio::null())
@@ -23,6 +39,73 @@ fn copy_to_clipboard(text: &str) {
 }
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) {
+    if app.device_link.is_some() {
+        if key.code == KeyCode::Char('q')
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        {
+            app.should_quit = true;
+        }
+        return;
+    }
+
+    if let Some(roster) = &app.group_roster {
+        match key.code {
+            KeyCode::Char('l') => app.request_invite_link(),
+            KeyCode::Char('y') => {
+                if let Some(link) = roster.invite_link.clone() {
+                    app.status_message = Some("Invite link copied".to_string());
+                    copy_to_clipboard(app, &link);
+                }
+            }
+            _ => app.group_roster = None,
+        }
+        return;
+    }
+
+    if let Some(view) = &app.safety_number {
+        if key.code == KeyCode::Char('y') {
+            let number = view.safety_number.clone();
+            app.status_message = Some("Safety number copied".to_string());
+            copy_to_clipboard(app, &number);
+        } else {
+            app.safety_number = None;
+        }
+        return;
+    }
+
+    if app.settings_open {
+        match key.code {
+            KeyCode::Char('c') => app.toggle_list_density(),
+            KeyCode::Char('H') => app.toggle_empty_conversations(),
+            KeyCode::Char('r') => app.toggle_read_receipts(),
+            KeyCode::Char('U') => {
+                app.settings_open = false;
+                app.start_unregister();
+            }
+            _ => app.settings_open = false,
+        }
+        return;
+    }
+
+    if app.reaction_details.is_some() {
+        app.reaction_details = None;
+        return;
+    }
+
+    if app.prompt.is_some() {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => app.resolve_prompt(true),
+            KeyCode::Esc | KeyCode::Char('n') => app.resolve_prompt(false),
+            _ => {}
+        }
+        return;
+    }
+
+    if app.text_prompt.is_some() {
+        handle_text_prompt_key(app, key);
+        return;
+    }
+
     // Global shortcuts
     match key {
         KeyEvent { code: KeyCode::Char('c'), modifiers, .. }
@@ -45,6 +128,18 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             return;
         }
         KeyEvent { code: KeyCode::Esc, .. } => {
+            if !app.view_once_copies.is_empty() {
+                app.dismiss_view_once();
+                return;
+            }
+            if app.focus == Focus::Input && app.voice_recording.is_some() {
+                app.cancel_voice_recording();
+                return;
+            }
+            if app.focus == Focus::Input && app.active_reply().is_some() {
+                app.pending_reply = None;
+                return;
+            }
             if app
                 .selected_conversation()
                 .is_some_and(|c| c.selection.is_some())
@@ -57,6 +152,12 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             app.filter_input.clear();
             app.focus = match app.focus {
                 Focus::Input => Focus::Messages,
+                Focus::FileBrowser
+                    if app.file_browser.purpose == FileBrowserPurpose::ExportAttachments =>
+                {
+                    app.file_browser.purpose = FileBrowserPurpose::Attach;
+                    Focus::Messages
+                }
                 Focus::FileBrowser => Focus::Input,
                 _ => Focus::Conversations,
             };
@@ -116,13 +217,121 @@ fn handle_conversations_key(app: &mut App, key: KeyEvent) {
             app.focus = Focus::Input;
         }
         KeyCode::Char('H') => {
-            app.show_empty_conversations = !app.show_empty_conversations;
+            app.toggle_empty_conversations();
+        }
+        KeyCode::Char('n') => {
+            app.start_alias_edit();
+        }
+        KeyCode::Char('m') => {
+            app.toggle_broadcast_target();
+        }
+        KeyCode::Char('M') => {
+            app.broadcast_targets.clear();
+        }
+        KeyCode::Char('a') => {
+            app.toggle_selected_archived();
+        }
+        KeyCode::Char('A') => {
+            app.show_archived = !app.show_archived;
+            app.ensure_selection_matches_filter();
+        }
+        KeyCode::Char('z') => {
+            app.toggle_selected_muted();
+        }
+        KeyCode::Char('c') => {
+            app.toggle_list_density();
+        }
+        KeyCode::Char('e') => {
+            app.start_expiration_edit();
+        }
+        KeyCode::Char('u') => {
+            app.mark_selected_unread();
+        }
+        KeyCode::Char('R') => {
+            app.refresh_avatars = true;
+        }
+        KeyCode::Char(',') => {
+            app.settings_open = true;
+        }
+        KeyCode::Char('P') => {
+            app.pending_account_list = true;
+        }
+        KeyCode::Char('G') => {
+            app.open_group_roster();
+        }
+        KeyCode::Char('N') => {
+            app.start_snooze_edit();
+        }
+        KeyCode::Char('V') => {
+            app.open_safety_number();
+        }
+        KeyCode::Char('C') => {
+            app.start_color_edit();
+        }
+        KeyCode::Char('o') => {
+            app.start_new_conversation();
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_paste(app: &mut App, text: &str) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    if let Some(prompt) = app.text_prompt.as_mut() {
+        prompt.input.insert_str(&text.replace('\n', " "));
+        return;
+    }
+    match app.focus {
+        Focus::Input => {
+            app.input.insert_str(&text);
+            app.update_typing();
+        }
+        Focus::ConversationFilter => {
+            app.filter_input.insert_str(&text.replace('\n', " "));
             app.ensure_selection_matches_filter();
         }
         _ => {}
     }
 }
 
+fn handle_text_prompt_key(app: &mut App, key: KeyEvent) {
+    let Some(input) = app.text_prompt.as_mut().map(|p| &mut p.input) else {
+        return;
+    };
+    match key {
+        KeyEvent { code: KeyCode::Enter, .. } => {
+            app.submit_text_prompt();
+        }
+        KeyEvent { code: KeyCode::Esc, .. } => {
+            app.text_prompt = None;
+        }
+        KeyEvent { code: KeyCode::Backspace, .. } => {
+            input.delete_back();
+        }
+        KeyEvent { code: KeyCode::Delete, .. } => {
+            input.delete_forward();
+        }
+        KeyEvent { code: KeyCode::Left, .. } => {
+            input.move_left();
+        }
+        KeyEvent { code: KeyCode::Right, .. } => {
+            input.move_right();
+        }
+        KeyEvent { code: KeyCode::Home, .. } => {
+            input.move_start();
+        }
+        KeyEvent { code: KeyCode::End, .. } => {
+            input.move_end();
+        }
+        KeyEvent { code: KeyCode::Char(c), modifiers, .. }
+            if !modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            input.insert(c);
+        }
+        _ => {}
+    }
+}
+
 fn handle_conversation_filter_key(app: &mut App, key: KeyEvent) {
     match key {
         KeyEvent { code: KeyCode::Enter, .. } => {
@@ -177,21 +386,9 @@ fn handle_messages_key(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Up | KeyCode::Char('k') => app.scroll_messages_up(),
         KeyCode::Down | KeyCode::Char('j') => app.scroll_messages_down(),
-        KeyCode::PageUp => {
-            for _ in 0..10 {
-                app.scroll_messages_up();
-            }
-        }
-        KeyCode::PageDown => {
-            for _ in 0..10 {
-                app.scroll_messages_down();
-            }
-        }
-        KeyCode::Home => {
-            for _ in 0..1000 {
-                app.scroll_messages_up();
-            }
-        }
+        KeyCode::PageUp => app.page_messages_up(),
+        KeyCode::PageDown => app.page_messages_down(),
+        KeyCode::Home => app.scroll_messages_to_top(),
         KeyCode::End => {
             if let Some(conv) = app.selected_conversation_mut() {
                 conv.scroll_to_bottom();
@@ -206,8 +403,13 @@ fn handle_messages_key(app: &mut App, key: KeyEvent) {
             }
         }
         KeyCode::Char('H') => {
-            app.show_empty_conversations = !app.show_empty_conversations;
-            app.ensure_selection_matches_filter();
+            app.toggle_empty_conversations();
+        }
+        KeyCode::Char('S') => {
+            app.open_attachment_export();
+        }
+        KeyCode::Char('t') => {
+            app.start_go_to_date();
         }
         _ => {}
     }
@@ -280,12 +482,58 @@ fn handle_selection_key(app: &mut App, key: KeyEvent) {
             ..
         } => {
             if let Some(text) = app.selected_conversation().and_then(|c| c.get_selected_text()) {
-                copy_to_clipboard(&text);
+                copy_to_clipboard(app, &text);
             }
             if let Some(conv) = app.selected_conversation_mut() {
                 conv.exit_selection_mode();
             }
         }
+        KeyEvent {
+            code: KeyCode::Char('Y'),
+            ..
+        } => {
+            let paths: Vec<String> = app
+                .selected_conversation()
+                .map(|c| c.get_selected_attachment_paths())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|p| resolve_attachment_path(p))
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+            if paths.is_empty() {
+                app.status_message = Some("No attachments in selection".to_string());
+            } else {
+                app.status_message = Some(format!("Copied {} attachment path(s)", paths.len()));
+                copy_to_clipboard(app, &paths.join("\n"));
+                if let Some(conv) = app.selected_conversation_mut() {
+                    conv.exit_selection_mode();
+                }
+            }
+        }
+        KeyEvent {
+            code: KeyCode::Char('Q'),
+            ..
+        } => {
+            app.reply_to_selected();
+        }
+        KeyEvent {
+            code: KeyCode::Char('g'),
+            ..
+        } => {
+            app.jump_to_quoted_message();
+        }
+        KeyEvent {
+            code: KeyCode::Char('R'),
+            ..
+        } => {
+            app.show_reaction_details();
+        }
+        KeyEvent {
+            code: KeyCode::Char('r'),
+            ..
+        } => {
+            app.retry_selected_send();
+        }
         KeyEvent {
             code: KeyCode::Char('d'),
             ..
@@ -301,40 +549,37 @@ fn handle_selection_key(app: &mut App, key: KeyEvent) {
             code: KeyCode::Char('D'),
             ..
         } => {
-            if let Some(conv) = app.selected_conversation_mut() {
-                let timestamps = conv.get_selected_outgoing_timestamps();
-                let target = conv.remote_delete_target();
-                let ids = conv.delete_selected_messages();
-                for id in &ids {
-                    let _ = app.storage.delete_message(id);
-                }
-                if let Some(target) = target {
-                    if !timestamps.is_empty() {
-                        app.pending_remote_deletes.push(PendingRemoteDelete {
-                            target,
-                            timestamps,
-                        });
-                    }
-                }
+            let count = app
+                .selected_conversation()
+                .and_then(|c| c.selection.as_ref())
+                .map_or(0, |s| s.range().count());
+            if count > 0 {
+                app.confirm(
+                    format!("Delete {} message(s) for everyone?", count),
+                    App::remote_delete_selected,
+                );
             }
         }
+        KeyEvent {
+            code: KeyCode::Char('p'),
+            ..
+        } => {
+            app.play_selected_audio();
+        }
         KeyEvent {
             code: KeyCode::Enter | KeyCode::Char('o'),
             ..
         } => {
+            let storage = app.storage.clone();
+            let view_once = app
+                .selected_conversation_mut()
+                .map(|c| c.open_selected_view_once(&storage, open_view_once))
+                .unwrap_or_default();
+            app.view_once_copies.extend(view_once);
             if let Some(conv) = app.selected_conversation() {
                 let paths = conv.get_selected_attachment_paths();
-                let attachments_dir = std::env::var("HOME")
-                    .map(|h| std::path::PathBuf::from(h).join(".local/share/signal-cli/attachments"))
-                    .ok();
-                for path in paths {
-                    let full_path = if path.starts_with('/') {
-                        std::path::PathBuf::from(&path)
-                    } else if let Some(ref dir) = attachments_dir {
-                        dir.join(&path)
-                    } else {
-                        continue;
-                    };
+                let paths = paths.iter().filter_map(|p| resolve_attachment_path(p));
+                for full_path in paths {
                     let _ = Command::new("xdg-open")
                         .arg(&full_path)
                         .stdout(Stdio::null())
@@ -350,6 +595,19 @@ fn handle_selection_key(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn open_view_once(path: &Path) -> bool {
+    let Ok(mut child) = Command::new("xdg-open")
+        .arg(path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    std::thread::spawn(move || child.wait());
+    true
+}
+
 fn handle_input_key(app: &mut App, key: KeyEvent) {
     match key {
         KeyEvent { code: KeyCode::Enter, .. } => {
@@ -360,9 +618,11 @@ fn handle_input_key(app: &mut App, key: KeyEvent) {
         }
         KeyEvent { code: KeyCode::Backspace, .. } => {
             app.input.delete_back();
+            app.update_typing();
         }
         KeyEvent { code: KeyCode::Delete, .. } => {
             app.input.delete_forward();
+            app.update_typing();
         }
         KeyEvent { code: KeyCode::Left, .. } => {
             app.input.move_left();
@@ -381,6 +641,7 @@ fn handle_input_key(app: &mut App, key: KeyEvent) {
             modifiers,
             ..
         } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.file_browser.purpose = FileBrowserPurpose::Attach;
             app.file_browser.refresh();
             app.focus = Focus::FileBrowser;
         }
@@ -389,19 +650,68 @@ fn handle_input_key(app: &mut App, key: KeyEvent) {
             modifiers,
             ..
         } if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.pending_attachments.clear();
+            app.discard_pending_attachments();
+        }
+        KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers,
+            ..
+        } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_voice_recording();
         }
         KeyEvent { code: KeyCode::Char(c), modifiers, .. }
             if !modifiers.contains(KeyModifiers::CONTROL) =>
         {
             app.input.insert(c);
+            app.update_typing();
         }
         _ => {}
     }
 }
 
 fn handle_file_browser_key(app: &mut App, key: KeyEvent) {
+    if app.file_browser.purpose == FileBrowserPurpose::ExportAttachments {
+        match key.code {
+            KeyCode::Char('s') => {
+                let dest = app.file_browser.current_dir.clone();
+                app.export_attachments(&dest);
+                return;
+            }
+            KeyCode::Char('q') => {
+                app.file_browser.purpose = FileBrowserPurpose::Attach;
+                app.focus = Focus::Messages;
+                return;
+            }
+            KeyCode::Enter | KeyCode::Char('l') => {
+                if app.file_browser.entries.get(app.file_browser.selected).is_some_and(|e| e.is_dir) {
+                    app.file_browser.enter_selected();
+                }
+                return;
+            }
+            KeyCode::Char(' ' | 'v' | 'V') => return,
+            _ => {}
+        }
+    }
+
+    let extends_range = matches!(key.code, KeyCode::Char('J' | 'K'))
+        || (matches!(key.code, KeyCode::Up | KeyCode::Down) && key.modifiers.contains(KeyModifiers::SHIFT));
+    if !extends_range {
+        app.file_browser.range_anchor = None;
+    }
+
     match key {
+        KeyEvent { code: KeyCode::Char('K'), .. } => {
+            app.file_browser.extend_range(-1);
+        }
+        KeyEvent { code: KeyCode::Char('J'), .. } => {
+            app.file_browser.extend_range(1);
+        }
+        KeyEvent { code: KeyCode::Up, modifiers, .. } if modifiers.contains(KeyModifiers::SHIFT) => {
+            app.file_browser.extend_range(-1);
+        }
+        KeyEvent { code: KeyCode::Down, modifiers, .. } if modifiers.contains(KeyModifiers::SHIFT) => {
+            app.file_browser.extend_range(1);
+        }
         KeyEvent { code: KeyCode::Up | KeyCode::Char('k'), .. } => {
             app.file_browser.move_selection(-1);
         }
@@ -431,6 +741,17 @@ fn handle_file_browser_key(app: &mut App, key: KeyEvent) {
         KeyEvent { code: KeyCode::Char('~'), .. } => {
             app.file_browser.go_home();
         }
+        KeyEvent { code: KeyCode::Char('b'), .. } => {
+            let dir = app.file_browser.current_dir.to_string_lossy().to_string();
+            app.status_message = Some(if app.file_browser.toggle_bookmark() {
+                format!("Bookmarked {}", dir)
+            } else {
+                format!("Removed bookmark {}", dir)
+            });
+        }
+        KeyEvent { code: KeyCode::Char('B'), .. } => {
+            app.file_browser.show_bookmarks();
+        }
         KeyEvent { code: KeyCode::Char('.'), .. } => {
             app.file_browser.toggle_hidden();
         }
@@ -1,5 +1,5 @@
 mod key;
 mod mouse;
 
-pub use key::handle_key_event;
+pub use key::{handle_key_event, handle_paste};
 pub use mouse::handle_mouse_event;
@@ -1,8 +1,6 @@
 use crate::app::{App, Focus, MessageSelection};
 use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 
-const ITEM_HEIGHT: u16 = 4;
-
 pub fn handle_mouse_event(app: &mut App, event: MouseEvent) {
     let x = event.column;
     let y = event.row;
@@ -42,16 +40,14 @@ fn handle_left_click(app: &mut App, x: u16, y: u16) {
         let list_area = app.layout_areas.conversations_list;
         let scroll_offset = app.layout_areas.conversations_scroll_offset;
         let relative_y = y.saturating_sub(list_area.y);
-        let item_index = (relative_y / ITEM_HEIGHT) as usize + scroll_offset;
+        let item_index = (relative_y / app.list_density.item_height()) as usize + scroll_offset;
 
         let filtered_indices = app.filtered_conversation_indices();
         if item_index < filtered_indices.len() {
             let new_selected = filtered_indices[item_index];
             if new_selected != app.selected {
                 app.selected = new_selected;
-                if app.conversations[app.selected].load_messages(&app.storage.clone()) {
-                    app.needs_image_preload = true;
-                }
+                app.load_selected_messages();
             }
         }
     } else if is_in_rect(x, y, app.layout_areas.messages) {
@@ -1,16 +1,22 @@
 use ratatui::layout::Rect;
 use ratatui_image::Resize;
+use ratatui_image::FontSize;
 use ratatui_image::picker::Picker;
 use ratatui_image::protocol::Protocol;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 
 struct CachedImage {
     protocol: Protocol,
     render_width: u16,
     render_height: u16,
+    max_width: u16,
+    generation: u64,
 }
 
 struct ProcessedImage {
@@ -18,46 +24,113 @@ struct ProcessedImage {
     protocol: Protocol,
     render_width: u16,
     render_height: u16,
+    max_width: u16,
+    generation: u64,
+}
+
+enum LoadRequest {
+    Load {
+        path: String,
+        max_width: u16,
+        generation: u64,
+    },
+    Thumbnail {
+        path: String,
+        generation: u64,
+    },
+    SetPicker(Picker),
+}
+
+enum LoadResult {
+    Loaded(ProcessedImage),
+    Thumbnail(ProcessedImage),
+    Missing(String),
+    Failed(String),
+    ThumbnailMissing(String),
+    ThumbnailFailed(String),
 }
 
 enum CacheEntry {
     Loading,
+    Downloading,
+    Missing(Instant),
+    Failed,
     Loaded(CachedImage),
 }
 
 pub struct ImageCache {
     cache: HashMap<String, CacheEntry>,
-    load_sender: Sender<(String, u16)>,
-    result_receiver: Receiver<Option<ProcessedImage>>,
+    reloading: HashSet<String>,
+    thumbnails: HashMap<String, CacheEntry>,
+    pending_downloads: Vec<String>,
+    missing_attempts: HashMap<String, u32>,
+    generation: u64,
+    load_sender: Sender<LoadRequest>,
+    result_receiver: Receiver<LoadResult>,
+    loaded: Arc<Notify>,
 }
 
 const MAX_IMAGE_WIDTH: u16 = 60;
 const MAX_IMAGE_HEIGHT: u16 = 20;
 const MIN_IMAGE_HEIGHT: u16 = 4;
+pub const THUMBNAIL_WIDTH: u16 = 6;
+pub const THUMBNAIL_HEIGHT: u16 = 2;
+const MISSING_RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_MISSING_ATTEMPTS: u32 = 10;
 
 impl ImageCache {
     pub fn new() -> Option<Self> {
         let picker = Picker::from_query_stdio().ok()?;
         let attachments_dir = get_attachments_dir()?;
 
-        let (load_sender, load_receiver) = mpsc::channel::<(String, u16)>();
-        let (result_sender, result_receiver) = mpsc::channel::<Option<ProcessedImage>>();
+        let (load_sender, load_receiver) = mpsc::channel::<LoadRequest>();
+        let (result_sender, result_receiver) = mpsc::channel::<LoadResult>();
+        let loaded = Arc::new(Notify::new());
+        let worker_loaded = loaded.clone();
 
         thread::spawn(move || {
-            let picker = picker;
-            while let Ok((path, max_width)) = load_receiver.recv() {
+            let mut picker = picker;
+            while let Ok(request) = load_receiver.recv() {
+                let (path, max_width, generation, thumbnail) = match request {
+                    LoadRequest::Load {
+                        path,
+                        max_width,
+                        generation,
+                    } => (path, max_width, generation, false),
+                    LoadRequest::Thumbnail { path, generation } => {
+                        (path, THUMBNAIL_WIDTH, generation, true)
+                    }
+                    LoadRequest::SetPicker(new_picker) => {
+                        picker = new_picker;
+                        continue;
+                    }
+                };
+
                 let full_path = if path.starts_with('/') {
                     PathBuf::from(&path)
                 } else {
                     attachments_dir.join(&path)
                 };
 
+                if !full_path.exists() {
+                    let _ = result_sender.send(if thumbnail {
+                        LoadResult::ThumbnailMissing(path)
+                    } else {
+                        LoadResult::Missing(path)
+                    });
+                    worker_loaded.notify_one();
+                    continue;
+                }
+
                 let result = (|| {
                     let data = std::fs::read(&full_path).ok()?;
                     let image = image::load_from_memory(&data).ok()?;
 
-                    let (render_width, render_height) =
-                        calculate_display_size(image.width(), image.height(), max_width);
+                    let (render_width, render_height) = if thumbnail {
+                        (THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)
+                    } else {
+                        calculate_display_size(image.width(), image.height(), max_width)
+                    };
                     let render_rect = Rect::new(0, 0, render_width, render_height);
 
                     let protocol = picker
@@ -65,39 +138,184 @@ impl ImageCache {
                         .ok()?;
 
                     Some(ProcessedImage {
-                        path,
+                        path: path.clone(),
                         protocol,
                         render_width,
                         render_height,
+                        max_width,
+                        generation,
                     })
                 })();
 
-                let _ = result_sender.send(result);
+                let _ = result_sender.send(match (result, thumbnail) {
+                    (Some(processed), false) => LoadResult::Loaded(processed),
+                    (Some(processed), true) => LoadResult::Thumbnail(processed),
+                    (None, false) => LoadResult::Failed(path),
+                    (None, true) => LoadResult::ThumbnailFailed(path),
+                });
+                worker_loaded.notify_one();
             }
         });
 
         Some(Self {
             cache: HashMap::new(),
+            reloading: HashSet::new(),
+            thumbnails: HashMap::new(),
+            pending_downloads: Vec::new(),
+            missing_attempts: HashMap::new(),
+            generation: 0,
             load_sender,
             result_receiver,
+            loaded,
         })
     }
 
+    pub fn set_picker(&mut self, picker: Picker) {
+        let _ = self.load_sender.send(LoadRequest::SetPicker(picker));
+        self.invalidate();
+    }
+
+    pub fn invalidate(&mut self) {
+        self.generation += 1;
+        self.reloading.clear();
+        self.thumbnails.clear();
+    }
+
+    fn request_load(&self, path: &str, max_width: u16) -> bool {
+        self.load_sender
+            .send(LoadRequest::Load {
+                path: path.to_string(),
+                max_width,
+                generation: self.generation,
+            })
+            .is_ok()
+    }
+
+    pub async fn wait_for_loaded_image(&self) {
+        self.loaded.notified().await;
+    }
+
     pub fn process_next_loaded_image(&mut self) -> bool {
-        if let Ok(result) = self.result_receiver.try_recv() {
-            if let Some(processed) = result {
+        let Ok(result) = self.result_receiver.try_recv() else {
+            return false;
+        };
+        match result {
+            LoadResult::Loaded(processed) => {
+                self.reloading.remove(&processed.path);
+                self.missing_attempts.remove(&processed.path);
                 self.cache.insert(
                     processed.path,
                     CacheEntry::Loaded(CachedImage {
                         protocol: processed.protocol,
                         render_width: processed.render_width,
                         render_height: processed.render_height,
+                        max_width: processed.max_width,
+                        generation: processed.generation,
                     }),
                 );
             }
-            return true;
+            LoadResult::Thumbnail(processed) => {
+                if processed.generation != self.generation {
+                    return true;
+                }
+                self.thumbnails.insert(
+                    processed.path,
+                    CacheEntry::Loaded(CachedImage {
+                        protocol: processed.protocol,
+                        render_width: processed.render_width,
+                        render_height: processed.render_height,
+                        max_width: processed.max_width,
+                        generation: processed.generation,
+                    }),
+                );
+            }
+            LoadResult::ThumbnailMissing(path) => {
+                let entry = self.missing_entry(&path);
+                self.thumbnails.insert(path, entry);
+            }
+            LoadResult::ThumbnailFailed(path) => {
+                self.thumbnails.insert(path, CacheEntry::Failed);
+            }
+            LoadResult::Missing(path) => {
+                self.reloading.remove(&path);
+                if !matches!(self.cache.get(&path), Some(CacheEntry::Loaded(_))) {
+                    if path.starts_with('/') {
+                        let entry = self.missing_entry(&path);
+                        self.cache.insert(path, entry);
+                    } else {
+                        self.cache.insert(path.clone(), CacheEntry::Downloading);
+                        self.pending_downloads.push(path);
+                    }
+                }
+            }
+            LoadResult::Failed(path) => {
+                self.reloading.remove(&path);
+                if !matches!(self.cache.get(&path), Some(CacheEntry::Loaded(_))) {
+                    self.cache.insert(path, CacheEntry::Failed);
+                }
+            }
         }
-        false
+        true
+    }
+
+    pub fn take_pending_downloads(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending_downloads)
+    }
+
+    pub fn download_finished(&mut self, path: &str, success: bool) {
+        if success {
+            self.thumbnails.remove(path);
+            self.cache.insert(path.to_string(), CacheEntry::Loading);
+            self.request_load(path, MAX_IMAGE_WIDTH);
+        } else {
+            let entry = self.missing_entry(path);
+            self.cache.insert(path.to_string(), entry);
+        }
+    }
+
+    fn missing_entry(&mut self, path: &str) -> CacheEntry {
+        let attempts = self.missing_attempts.entry(path.to_string()).or_insert(0);
+        *attempts += 1;
+        if *attempts >= MAX_MISSING_ATTEMPTS {
+            CacheEntry::Failed
+        } else {
+            CacheEntry::Missing(Instant::now() + MISSING_RETRY_DELAY)
+        }
+    }
+
+    pub fn next_retry(&self) -> Option<Instant> {
+        self.cache
+            .values()
+            .chain(self.thumbnails.values())
+            .filter_map(|entry| match entry {
+                CacheEntry::Missing(retry_at) => Some(*retry_at),
+                _ => None,
+            })
+            .min()
+    }
+
+    pub fn retry_missing(&mut self) -> bool {
+        let now = Instant::now();
+        let due = |map: &HashMap<String, CacheEntry>| -> Vec<String> {
+            map.iter()
+                .filter(|(_, entry)| matches!(entry, CacheEntry::Missing(at) if *at <= now))
+                .map(|(path, _)| path.clone())
+                .collect()
+        };
+        let images = due(&self.cache);
+        let thumbnails = due(&self.thumbnails);
+        for path in &images {
+            self.cache.insert(path.clone(), CacheEntry::Loading);
+            self.request_load(path, MAX_IMAGE_WIDTH);
+        }
+        for path in &thumbnails {
+            self.thumbnails.insert(path.clone(), CacheEntry::Loading);
+            let _ = self.load_sender.send(LoadRequest::Thumbnail {
+                path: path.clone(),
+                generation: self.generation,
+            });
+        }
+        !images.is_empty() || !thumbnails.is_empty()
     }
 
     pub fn get_image_with_size(
@@ -107,10 +325,19 @@ impl ImageCache {
     ) -> Option<(&Protocol, u16, u16)> {
         if !self.cache.contains_key(path) {
             self.cache.insert(path.to_string(), CacheEntry::Loading);
-            let _ = self.load_sender.send((path.to_string(), max_width));
+            self.request_load(path, max_width);
             return None;
         }
 
+        if let Some(CacheEntry::Loaded(cached)) = self.cache.get(path)
+            && (cached.generation != self.generation
+                || cached.max_width.min(MAX_IMAGE_WIDTH) != max_width.min(MAX_IMAGE_WIDTH))
+            && !self.reloading.contains(path)
+            && self.request_load(path, max_width)
+        {
+            self.reloading.insert(path.to_string());
+        }
+
         match self.cache.get(path) {
             Some(CacheEntry::Loaded(cached)) => {
                 Some((&cached.protocol, cached.render_width, cached.render_height))
@@ -119,8 +346,32 @@ impl ImageCache {
         }
     }
 
+    pub fn get_thumbnail(&mut self, path: &str) -> Option<&Protocol> {
+        if !self.thumbnails.contains_key(path) {
+            self.thumbnails.insert(path.to_string(), CacheEntry::Loading);
+            let _ = self.load_sender.send(LoadRequest::Thumbnail {
+                path: path.to_string(),
+                generation: self.generation,
+            });
+            return None;
+        }
+
+        match self.thumbnails.get(path) {
+            Some(CacheEntry::Loaded(cached)) => Some(&cached.protocol),
+            _ => None,
+        }
+    }
+
     pub fn is_loading(&self, path: &str) -> bool {
-        matches!(self.cache.get(path), Some(CacheEntry::Loading))
+        matches!(self.cache.get(path), Some(CacheEntry::Loading | CacheEntry::Missing(_)))
+    }
+
+    pub fn is_downloading(&self, path: &str) -> bool {
+        matches!(self.cache.get(path), Some(CacheEntry::Downloading))
+    }
+
+    pub fn is_failed(&self, path: &str) -> bool {
+        matches!(self.cache.get(path), Some(CacheEntry::Failed))
     }
 
     pub fn get_image_height(&self, path: &str) -> u16 {
@@ -138,7 +389,7 @@ impl ImageCache {
         for path in paths {
             if !self.cache.contains_key(path) {
                 self.cache.insert(path.clone(), CacheEntry::Loading);
-                let _ = self.load_sender.send((path.clone(), max_width));
+                self.request_load(path, max_width);
             }
         }
     }
@@ -169,6 +420,14 @@ fn calculate_display_size(img_width: u32, img_height: u32, max_width: u16) -> (u
     (display_width, display_height)
 }
 
+pub fn terminal_font_size() -> Option<FontSize> {
+    let size = crossterm::terminal::window_size().ok()?;
+    if size.columns == 0 || size.rows == 0 || size.width == 0 || size.height == 0 {
+        return None;
+    }
+    Some((size.width / size.columns, size.height / size.rows))
+}
+
 fn get_attachments_dir() -> Option<PathBuf> {
     let home = std::env::var("HOME").ok()?;
     let dir = PathBuf::from(home).join(".local/share/signal-cli/attachments");
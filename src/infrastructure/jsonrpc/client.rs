@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, warn};
 use uuid::Uuid;
 
@@ -24,20 +25,18 @@ pub struct JsonRpcClient<T: Transport> {
     transport: Arc<T>,
     pending_requests: Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>,
     notification_sender: broadcast::Sender<JsonRpcNotification>,
+    receiver: std::sync::Mutex<Option<JoinHandle<()>>>,
     timeout: Duration,
 }
 
 impl<T: Transport + 'static> JsonRpcClient<T> {
-    pub fn new(transport: T) -> Self {
-        Self::with_timeout(transport, Duration::from_secs(30))
-    }
-
     pub fn with_timeout(transport: T, timeout: Duration) -> Self {
         let (notification_sender, _) = broadcast::channel(256);
         Self {
             transport: Arc::new(transport),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
             notification_sender,
+            receiver: std::sync::Mutex::new(None),
             timeout,
         }
     }
@@ -48,12 +47,19 @@ impl<T: Transport + 'static> JsonRpcClient<T> {
         Ok(())
     }
 
+    pub async fn reconnect(&self) -> Result<(), RpcError> {
+        let _ = self.transport.disconnect().await;
+        self.transport.connect().await?;
+        self.spawn_receiver();
+        Ok(())
+    }
+
     fn spawn_receiver(&self) {
         let transport = self.transport.clone();
         let pending = self.pending_requests.clone();
         let notif_sender = self.notification_sender.clone();
 
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             loop {
                 match transport.receive().await {
                     Ok(data) => {
@@ -82,7 +88,7 @@ impl<T: Transport + 'static> JsonRpcClient<T> {
                                 if notif_sender.send(notification).is_err() {}
                             }
                             Err(e) => {
-                                error!("Failed to parse JSON-RPC message: {} - data: {}", e, data_str);
+                                warn!("Failed to parse JSON-RPC message: {} - data: {}", e, data_str);
                             }
                         }
                     }
@@ -93,16 +99,21 @@ impl<T: Transport + 'static> JsonRpcClient<T> {
                 }
             }
         });
+        if let Some(previous) = self.receiver.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
     }
 
     fn generate_id() -> String {
         Uuid::new_v4().to_string()
     }
-}
 
-#[async_trait]
-impl<T: Transport + 'static> RpcClient for JsonRpcClient<T> {
-    async fn call<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    pub async fn call_with_timeout<P, R>(
+        &self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R, RpcError>
     where
         P: Serialize + Send,
         R: DeserializeOwned,
@@ -128,7 +139,7 @@ impl<T: Transport + 'static> RpcClient for JsonRpcClient<T> {
 
         self.transport.send(request_json.as_bytes()).await?;
 
-        let response = tokio::time::timeout(self.timeout, rx)
+        let response = tokio::time::timeout(timeout, rx)
             .await
             .map_err(|_| {
                 let pending = self.pending_requests.clone();
@@ -152,6 +163,17 @@ impl<T: Transport + 'static> RpcClient for JsonRpcClient<T> {
             RpcError::InvalidResponse(format!("Failed to deserialize result: {}", e))
         })
     }
+}
+
+#[async_trait]
+impl<T: Transport + 'static> RpcClient for JsonRpcClient<T> {
+    async fn call<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    where
+        P: Serialize + Send,
+        R: DeserializeOwned,
+    {
+        self.call_with_timeout(method, params, self.timeout).await
+    }
 
     fn notifications(&self) -> broadcast::Receiver<JsonRpcNotification> {
         self.notification_sender.subscribe()
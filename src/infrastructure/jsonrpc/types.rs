@@ -109,4 +109,7 @@ pub mod error_codes {
     pub const METHOD_NOT_FOUND: i32 = -32601;
     pub const INVALID_PARAMS: i32 = -32602;
     pub const INTERNAL_ERROR: i32 = -32603;
+
+    pub const UNTRUSTED_KEY_ERROR: i32 = -4;
+    pub const RATE_LIMIT_ERROR: i32 = -5;
 }
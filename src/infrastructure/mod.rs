@@ -2,6 +2,12 @@ pub mod jsonrpc;
 pub mod signal;
 pub mod transport;
 
-pub use signal::client::SignalClient;
+pub use signal::client::{RpcTimeouts, SignalClient};
+pub use signal::error::SignalError;
 pub use signal::repository::SignalRepository;
 pub use signal::types::*;
+pub use transport::DEFAULT_MAX_LINE_BYTES;
+#[cfg(feature = "dbus")]
+pub use transport::DbusTransport;
+#[cfg(feature = "rest")]
+pub use transport::RestTransport;
@@ -1,46 +1,103 @@
 use super::error::SignalError;
 use super::repository::SignalRepository;
 use super::types::*;
-use crate::infrastructure::jsonrpc::{JsonRpcClient, JsonRpcNotification, RpcClient};
-use crate::infrastructure::transport::StdioTransport;
+use crate::infrastructure::jsonrpc::{
+    JsonRpcClient, JsonRpcNotification, RpcClient, RpcError, error_codes,
+};
+use crate::infrastructure::transport::{StdioTransport, Transport};
 use async_trait::async_trait;
+use base64::prelude::*;
 use serde::Serialize;
 use serde_json::Value;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
-use tracing::{debug, error, info};
+use tracing::{debug, info, warn};
+
+pub fn normalize_recipient(recipient: &str) -> Result<String, SignalError> {
+    if uuid::Uuid::parse_str(recipient).is_ok() {
+        return Ok(recipient.to_lowercase());
+    }
+    crate::util::normalize_e164(recipient)
+        .ok_or_else(|| SignalError::InvalidRecipient(recipient.to_string()))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RpcTimeouts {
+    pub default: Duration,
+    pub attachments: Duration,
+}
+
+impl Default for RpcTimeouts {
+    fn default() -> Self {
+        Self {
+            default: Duration::from_secs(30),
+            attachments: Duration::from_secs(300),
+        }
+    }
+}
+
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+const LINK_TIMEOUT: Duration = Duration::from_secs(300);
 
 pub struct SignalClient {
-    rpc: Arc<JsonRpcClient<StdioTransport>>,
+    rpc: Arc<JsonRpcClient<Box<dyn Transport>>>,
     account: Option<String>,
+    timeouts: RpcTimeouts,
     connected: AtomicBool,
     message_sender: broadcast::Sender<IncomingMessage>,
 }
 
 impl SignalClient {
-    pub fn new(account: Option<String>) -> Self {
-        let transport = StdioTransport::new(account.clone());
-        let rpc = Arc::new(JsonRpcClient::new(transport));
+    pub fn new(account: Option<String>, timeouts: RpcTimeouts, max_line_bytes: usize) -> Self {
+        let transport = StdioTransport::new(account.clone(), max_line_bytes);
+        Self::with_transport(transport, account, timeouts)
+    }
+
+    pub fn with_transport(transport: impl Transport + 'static, account: Option<String>, timeouts: RpcTimeouts) -> Self {
+        let transport: Box<dyn Transport> = Box::new(transport);
+        let rpc = Arc::new(JsonRpcClient::with_timeout(transport, timeouts.default));
         let (message_sender, _) = broadcast::channel(256);
 
         Self {
             rpc,
             account,
+            timeouts,
             connected: AtomicBool::new(false),
             message_sender,
         }
     }
 
+    pub async fn ping(&self) -> Result<(), SignalError> {
+        let _: Value = self
+            .call_with_timeout("version", EmptyParams::default(), PING_TIMEOUT)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn reconnect(&self) -> Result<(), SignalError> {
+        self.connected.store(false, Ordering::SeqCst);
+        self.rpc.reconnect().await?;
+        self.connected.store(true, Ordering::SeqCst);
+        info!("Reconnected to signal-cli daemon");
+        Ok(())
+    }
+
     fn spawn_notification_handler(&self) {
         let mut notifications = self.rpc.notifications();
         let message_sender = self.message_sender.clone();
+        let account = self.account.clone();
 
         tokio::spawn(async move {
             loop {
                 match notifications.recv().await {
                     Ok(notification) => {
                         if let Some(message) = Self::parse_notification(notification) {
+                            if !Self::is_for_account(&message, account.as_deref()) {
+                                debug!("Ignoring notification for account {:?}", message.account);
+                                continue;
+                            }
                             if message_sender.send(message).is_err() {}
                         }
                     }
@@ -56,18 +113,54 @@ impl SignalClient {
         });
     }
 
+    fn is_for_account(message: &IncomingMessage, account: Option<&str>) -> bool {
+        match (account, message.account.as_deref()) {
+            (Some(active), Some(target)) => active == target,
+            _ => true,
+        }
+    }
+
     fn parse_notification(notification: JsonRpcNotification) -> Option<IncomingMessage> {
-        if notification.method == "receive"
-            && let Some(params) = notification.params
-        {
-            match serde_json::from_value::<IncomingMessage>(params) {
-                Ok(msg) => return Some(msg),
-                Err(e) => {
-                    error!("Failed to parse incoming message: {}", e);
-                }
+        let params = notification.params?;
+        let parsed = match notification.method.as_str() {
+            "receive" => serde_json::from_value::<IncomingMessage>(params),
+            "receipt" | "typing" if params.get("envelope").is_some() => {
+                serde_json::from_value::<IncomingMessage>(params)
+            }
+            "receipt" => Self::parse_flat_envelope(params, |envelope, value| {
+                envelope.receipt_message = Some(serde_json::from_value(value)?);
+                Ok(())
+            }),
+            "typing" => Self::parse_flat_envelope(params, |envelope, value| {
+                envelope.typing_message = Some(serde_json::from_value(value)?);
+                Ok(())
+            }),
+            other => {
+                debug!("Ignoring notification: {}", other);
+                return None;
+            }
+        };
+
+        match parsed {
+            Ok(msg) => Some(msg),
+            Err(e) => {
+                warn!("Failed to parse {} notification: {}", notification.method, e);
+                None
             }
         }
-        None
+    }
+
+    fn parse_flat_envelope(
+        params: Value,
+        attach: impl FnOnce(&mut Envelope, Value) -> Result<(), serde_json::Error>,
+    ) -> Result<IncomingMessage, serde_json::Error> {
+        let mut envelope: Envelope = serde_json::from_value(params.clone())?;
+        attach(&mut envelope, params.clone())?;
+        let account = params
+            .get("account")
+            .and_then(Value::as_str)
+            .map(String::from);
+        Ok(IncomingMessage { envelope, account })
     }
 
     async fn call<P, R>(&self, method: &str, params: P) -> Result<R, SignalError>
@@ -76,7 +169,96 @@ impl SignalClient {
         R: serde::de::DeserializeOwned,
     {
         let params_value = serde_json::to_value(params)?;
-        self.rpc.call(method, params_value).await.map_err(|e| e.into())
+        self.rpc
+            .call(method, params_value.clone())
+            .await
+            .map_err(|e| Self::translate_error(e, &params_value))
+    }
+
+    async fn call_with_timeout<P, R>(
+        &self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R, SignalError>
+    where
+        P: Serialize + Send,
+        R: serde::de::DeserializeOwned,
+    {
+        let params_value = serde_json::to_value(params)?;
+        self.rpc
+            .call_with_timeout(method, params_value.clone(), timeout)
+            .await
+            .map_err(|e| Self::translate_error(e, &params_value))
+    }
+
+    fn translate_error(err: RpcError, params: &Value) -> SignalError {
+        let RpcError::RpcError { code, message, data } = &err else {
+            return err.into();
+        };
+
+        let lower = message.to_lowercase();
+        let results: &[Value] = data
+            .as_ref()
+            .and_then(|d| d.pointer("/response/results"))
+            .and_then(|r| r.as_array())
+            .map_or(&[], |r| r.as_slice());
+        let failure = |kind: &str| {
+            results
+                .iter()
+                .find(|r| r["type"].as_str() == Some(kind))
+        };
+
+        if *code == error_codes::RATE_LIMIT_ERROR
+            || lower.contains("rate limit")
+            || failure("RATE_LIMIT_FAILURE").is_some()
+        {
+            let retry_after = results
+                .iter()
+                .find_map(|r| r["retryAfterSeconds"].as_u64())
+                .or_else(|| data.as_ref().and_then(|d| d["retryAfterSeconds"].as_u64()))
+                .unwrap_or(0);
+            return SignalError::RateLimited { retry_after };
+        }
+
+        if lower.contains("captcha")
+            || lower.contains("proof required")
+            || failure("PROOF_REQUIRED_FAILURE").is_some()
+        {
+            return SignalError::CaptchaRequired;
+        }
+
+        if *code == error_codes::UNTRUSTED_KEY_ERROR
+            || lower.contains("untrusted identity")
+            || failure("IDENTITY_FAILURE").is_some()
+        {
+            let address = failure("IDENTITY_FAILURE")
+                .and_then(|r| {
+                    let addr = &r["recipientAddress"];
+                    addr["number"].as_str().or_else(|| addr["uuid"].as_str())
+                })
+                .or_else(|| params["recipient"][0].as_str())
+                .unwrap_or_default()
+                .to_string();
+            return SignalError::UntrustedIdentity { address };
+        }
+
+        if lower.contains("group not found")
+            || lower.contains("invalid group id")
+            || lower.contains("unknown group")
+        {
+            let group_id = params["groupId"].as_str().unwrap_or(message).to_string();
+            return SignalError::GroupNotFound(group_id);
+        }
+
+        if lower.contains("not an admin")
+            || lower.contains("not allowed")
+            || lower.contains("permission")
+        {
+            return SignalError::PermissionDenied(message.clone());
+        }
+
+        err.into()
     }
 }
 
@@ -117,58 +299,88 @@ impl SignalRepository for SignalClient {
         self.call("listAccounts", EmptyParams::default()).await
     }
 
-    async fn send_message(&self, recipient: &str, message: &str) -> Result<SendResult, SignalError> {
+    async fn unregister(&self, delete_account: bool) -> Result<(), SignalError> {
         #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
         struct Params {
-            recipient: Vec<String>,
-            message: String,
+            delete_account: bool,
         }
 
-        self.call(
-            "send",
-            Params {
-                recipient: vec![recipient.to_string()],
-                message: message.to_string(),
-            },
-        )
-        .await
+        let _: Value = self.call("unregister", Params { delete_account }).await?;
+        Ok(())
     }
 
-    async fn send_group_message(&self, group_id: &str, message: &str) -> Result<SendResult, SignalError> {
-        #[derive(Serialize)]
+    async fn delete_local_account_data(&self) -> Result<(), SignalError> {
+        let _: Value = self
+            .call("deleteLocalAccountData", EmptyParams::default())
+            .await?;
+        Ok(())
+    }
+
+    async fn start_link(&self) -> Result<String, SignalError> {
+        #[derive(serde::Deserialize)]
         #[serde(rename_all = "camelCase")]
-        struct Params {
-            group_id: String,
-            message: String,
+        struct LinkResult {
+            device_link_uri: String,
         }
 
-        self.call(
-            "send",
-            Params {
-                group_id: group_id.to_string(),
-                message: message.to_string(),
-            },
-        )
-        .await
+        let result: LinkResult = self.call("startLink", EmptyParams::default()).await?;
+        Ok(result.device_link_uri)
     }
 
-    async fn send_message_with_attachments(&self, recipient: &str, message: &str, attachments: Vec<String>) -> Result<SendResult, SignalError> {
+    async fn finish_link(&self, device_link_uri: &str, device_name: &str) -> Result<String, SignalError> {
         #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
         struct Params {
-            recipient: Vec<String>,
-            message: String,
-            attachments: Vec<String>,
+            device_link_uri: String,
+            device_name: String,
         }
 
-        self.call(
-            "send",
-            Params {
-                recipient: vec![recipient.to_string()],
-                message: message.to_string(),
-                attachments,
-            },
-        )
-        .await
+        #[derive(serde::Deserialize)]
+        struct LinkResult {
+            number: String,
+        }
+
+        let result: LinkResult = self
+            .call_with_timeout(
+                "finishLink",
+                Params {
+                    device_link_uri: device_link_uri.to_string(),
+                    device_name: device_name.to_string(),
+                },
+                LINK_TIMEOUT,
+            )
+            .await?;
+        Ok(result.number)
+    }
+
+    async fn send(&self, mut params: SendMessageParams) -> Result<SendResult, SignalError> {
+        if let Some(recipients) = params.recipient.take() {
+            let normalized = recipients
+                .iter()
+                .map(|r| normalize_recipient(r))
+                .collect::<Result<Vec<_>, _>>()?;
+            params.recipient = Some(normalized);
+        }
+        let timeout = if params.attachments.is_some() {
+            self.timeouts.attachments
+        } else {
+            self.timeouts.default
+        };
+        self.call_with_timeout("send", params, timeout).await
+    }
+
+    async fn send_message(&self, recipient: &str, message: &str) -> Result<SendResult, SignalError> {
+        self.send(SendMessageParams::direct(recipient, message)).await
+    }
+
+    async fn send_group_message(&self, group_id: &str, message: &str) -> Result<SendResult, SignalError> {
+        self.send(SendMessageParams::group(group_id, message)).await
+    }
+
+    async fn send_message_with_attachments(&self, recipient: &str, message: &str, attachments: Vec<String>) -> Result<SendResult, SignalError> {
+        self.send(SendMessageParams::direct(recipient, message).with_attachments(attachments))
+            .await
     }
 
     async fn send_reaction(&self, recipient: &str, emoji: &str, target_author: &str, target_timestamp: i64) -> Result<(), SignalError> {
@@ -252,6 +464,40 @@ impl SignalRepository for SignalClient {
         Ok(())
     }
 
+    async fn set_expiration(&self, identifier: &str, seconds: u32) -> Result<(), SignalError> {
+        #[derive(Serialize)]
+        struct Params {
+            recipient: String,
+            expiration: u32,
+        }
+
+        let _: Value = self
+            .call(
+                "updateContact",
+                Params {
+                    recipient: identifier.to_string(),
+                    expiration: seconds,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_user_status(&self, recipients: &[String]) -> Result<Vec<UserStatus>, SignalError> {
+        #[derive(Serialize)]
+        struct Params {
+            recipient: Vec<String>,
+        }
+
+        self.call(
+            "getUserStatus",
+            Params {
+                recipient: recipients.to_vec(),
+            },
+        )
+        .await
+    }
+
     async fn block_contact(&self, identifier: &str) -> Result<(), SignalError> {
         #[derive(Serialize)]
         struct Params {
@@ -334,6 +580,54 @@ impl SignalRepository for SignalClient {
         Ok(())
     }
 
+    async fn set_group_expiration(&self, group_id: &str, seconds: u32) -> Result<(), SignalError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            group_id: String,
+            expiration: u32,
+        }
+
+        let _: Value = self
+            .call(
+                "updateGroup",
+                Params {
+                    group_id: group_id.to_string(),
+                    expiration: seconds,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_group_invite_link(&self, group_id: &str) -> Result<String, SignalError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            group_id: String,
+            link: String,
+        }
+
+        let group = self.get_group(group_id).await?;
+        if let Some(link) = group.group_invite_link {
+            return Ok(link);
+        }
+
+        let _: Value = self
+            .call(
+                "updateGroup",
+                Params {
+                    group_id: group_id.to_string(),
+                    link: "enabled".to_string(),
+                },
+            )
+            .await?;
+        self.get_group(group_id)
+            .await?
+            .group_invite_link
+            .ok_or_else(|| SignalError::Unknown("signal-cli returned no invite link".to_string()))
+    }
+
     async fn list_identities(&self) -> Result<Vec<Identity>, SignalError> {
         self.call("listIdentities", EmptyParams::default()).await
     }
@@ -394,6 +688,44 @@ impl SignalRepository for SignalClient {
         Ok(())
     }
 
+    async fn send_group_typing_started(&self, group_id: &str) -> Result<(), SignalError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            group_id: String,
+        }
+
+        let _: Value = self
+            .call(
+                "sendTyping",
+                Params {
+                    group_id: group_id.to_string(),
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn send_group_typing_stopped(&self, group_id: &str) -> Result<(), SignalError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            group_id: String,
+            stop: bool,
+        }
+
+        let _: Value = self
+            .call(
+                "sendTyping",
+                Params {
+                    group_id: group_id.to_string(),
+                    stop: true,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
     async fn send_read_receipt(&self, recipient: &str, timestamps: Vec<i64>) -> Result<(), SignalError> {
         #[derive(Serialize)]
         #[serde(rename_all = "camelCase")]
@@ -454,6 +786,38 @@ impl SignalRepository for SignalClient {
         Ok(())
     }
 
+    async fn download_attachment(&self, id: &str, recipient: Option<&str>, group_id: Option<&str>) -> Result<Vec<u8>, SignalError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            recipient: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            group_id: Option<String>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AttachmentData {
+            data: String,
+        }
+
+        let result: AttachmentData = self
+            .call_with_timeout(
+                "getAttachment",
+                Params {
+                    id: id.to_string(),
+                    recipient: recipient.map(String::from),
+                    group_id: group_id.map(String::from),
+                },
+                self.timeouts.attachments,
+            )
+            .await?;
+        BASE64_STANDARD
+            .decode(result.data)
+            .map_err(|e| SignalError::Unknown(format!("Invalid attachment data: {}", e)))
+    }
+
     fn incoming_messages(&self) -> broadcast::Receiver<IncomingMessage> {
         self.message_sender.subscribe()
     }
@@ -464,3 +828,150 @@ impl From<serde_json::Error> for SignalError {
         SignalError::Rpc(crate::infrastructure::jsonrpc::RpcError::Serialization(e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn notification(value: Value) -> JsonRpcNotification {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn accepts_uuids_and_lowercases_them() {
+        assert_eq!(
+            normalize_recipient("A1B2C3D4-0000-4000-8000-00000000000F").unwrap(),
+            "a1b2c3d4-0000-4000-8000-00000000000f"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_numbers_with_invalid_recipient() {
+        assert_eq!(normalize_recipient("+1 555 010 0000").unwrap(), "+15550100000");
+        for bad in ["5550100", "+1-800-FLOWERS", "a1b2c3d4-0000", ""] {
+            assert!(matches!(normalize_recipient(bad), Err(SignalError::InvalidRecipient(r)) if r == bad));
+        }
+    }
+
+    #[test]
+    fn parses_receive_notifications() {
+        let message = SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "receive",
+            "params": {
+                "account": "+15550000001",
+                "envelope": {
+                    "sourceUuid": "a1b2",
+                    "timestamp": 1700000000000i64,
+                    "dataMessage": { "message": "hi" }
+                }
+            }
+        })))
+        .unwrap();
+        assert_eq!(message.account.as_deref(), Some("+15550000001"));
+        assert_eq!(message.envelope.source_uuid.as_deref(), Some("a1b2"));
+        assert!(message.envelope.data_message.is_some());
+    }
+
+    #[test]
+    fn parses_top_level_receipt_notifications() {
+        let message = SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "receipt",
+            "params": {
+                "account": "+15550000001",
+                "sourceUuid": "a1b2",
+                "timestamp": 1700000000001i64,
+                "type": "READ",
+                "timestamps": [1700000000000i64]
+            }
+        })))
+        .unwrap();
+        let receipt = message.envelope.receipt_message.unwrap();
+        assert_eq!(receipt.receipt_type.as_deref(), Some("READ"));
+        assert_eq!(receipt.timestamps, vec![1700000000000]);
+        assert_eq!(message.account.as_deref(), Some("+15550000001"));
+    }
+
+    #[test]
+    fn parses_top_level_typing_notifications() {
+        let message = SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "typing",
+            "params": {
+                "sourceUuid": "a1b2",
+                "action": "STARTED",
+                "timestamp": 1700000000000i64
+            }
+        })))
+        .unwrap();
+        let typing = message.envelope.typing_message.unwrap();
+        assert_eq!(typing.action.as_deref(), Some("STARTED"));
+    }
+
+    #[test]
+    fn parses_receipts_nested_in_an_envelope() {
+        let message = SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "receipt",
+            "params": {
+                "envelope": {
+                    "sourceUuid": "a1b2",
+                    "receiptMessage": { "type": "DELIVERY", "timestamps": [1] }
+                }
+            }
+        })))
+        .unwrap();
+        assert!(message.envelope.receipt_message.is_some());
+    }
+
+    #[test]
+    fn ignores_unknown_and_malformed_notifications() {
+        assert!(SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "somethingNew",
+            "params": { "envelope": {} }
+        })))
+        .is_none());
+        assert!(SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "receive",
+            "params": { "envelope": "not an object" }
+        })))
+        .is_none());
+        assert!(SignalClient::parse_notification(notification(json!({
+            "jsonrpc": "2.0",
+            "method": "receive"
+        })))
+        .is_none());
+    }
+
+    #[test]
+    fn keeps_only_notifications_for_the_active_account() {
+        let messages: Vec<IncomingMessage> = [Some("+15550000001"), Some("+15550000002"), Some("+15550000001"), None]
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, account)| {
+                SignalClient::parse_notification(notification(json!({
+                    "jsonrpc": "2.0",
+                    "method": "receive",
+                    "params": {
+                        "account": account,
+                        "envelope": { "sourceUuid": format!("sender-{i}"), "timestamp": i }
+                    }
+                })))
+            })
+            .collect();
+        assert_eq!(messages.len(), 4);
+
+        let kept: Vec<&str> = messages
+            .iter()
+            .filter(|m| SignalClient::is_for_account(m, Some("+15550000001")))
+            .filter_map(|m| m.envelope.source_uuid.as_deref())
+            .collect();
+        assert_eq!(kept, ["sender-0", "sender-2", "sender-3"]);
+
+        assert!(messages.iter().all(|m| SignalClient::is_for_account(m, None)));
+    }
+}
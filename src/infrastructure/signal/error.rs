@@ -30,6 +30,9 @@ pub enum SignalError {
     #[error("Untrusted identity for {address}")]
     UntrustedIdentity { address: String },
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Proof of captcha required")]
     CaptchaRequired,
 
@@ -11,7 +11,12 @@ pub trait SignalRepository: Send + Sync {
 
     async fn get_account_info(&self) -> Result<Account, SignalError>;
     async fn list_accounts(&self) -> Result<Vec<String>, SignalError>;
+    async fn unregister(&self, delete_account: bool) -> Result<(), SignalError>;
+    async fn delete_local_account_data(&self) -> Result<(), SignalError>;
+    async fn start_link(&self) -> Result<String, SignalError>;
+    async fn finish_link(&self, device_link_uri: &str, device_name: &str) -> Result<String, SignalError>;
 
+    async fn send(&self, params: SendMessageParams) -> Result<SendResult, SignalError>;
     async fn send_message(&self, recipient: &str, message: &str) -> Result<SendResult, SignalError>;
     async fn send_group_message(&self, group_id: &str, message: &str) -> Result<SendResult, SignalError>;
     async fn send_message_with_attachments(&self, recipient: &str, message: &str, attachments: Vec<String>) -> Result<SendResult, SignalError>;
@@ -21,6 +26,8 @@ pub trait SignalRepository: Send + Sync {
     async fn list_contacts(&self) -> Result<Vec<Contact>, SignalError>;
     async fn get_contact(&self, identifier: &str) -> Result<Contact, SignalError>;
     async fn update_contact_name(&self, identifier: &str, name: &str) -> Result<(), SignalError>;
+    async fn set_expiration(&self, identifier: &str, seconds: u32) -> Result<(), SignalError>;
+    async fn get_user_status(&self, recipients: &[String]) -> Result<Vec<UserStatus>, SignalError>;
     async fn block_contact(&self, identifier: &str) -> Result<(), SignalError>;
     async fn unblock_contact(&self, identifier: &str) -> Result<(), SignalError>;
 
@@ -28,17 +35,23 @@ pub trait SignalRepository: Send + Sync {
     async fn get_group(&self, group_id: &str) -> Result<Group, SignalError>;
     async fn leave_group(&self, group_id: &str) -> Result<(), SignalError>;
     async fn block_group(&self, group_id: &str) -> Result<(), SignalError>;
+    async fn set_group_expiration(&self, group_id: &str, seconds: u32) -> Result<(), SignalError>;
+    async fn get_group_invite_link(&self, group_id: &str) -> Result<String, SignalError>;
 
     async fn list_identities(&self) -> Result<Vec<Identity>, SignalError>;
     async fn trust_identity(&self, identifier: &str, trust_all_keys: bool) -> Result<(), SignalError>;
 
     async fn send_typing_started(&self, recipient: &str) -> Result<(), SignalError>;
     async fn send_typing_stopped(&self, recipient: &str) -> Result<(), SignalError>;
+    async fn send_group_typing_started(&self, group_id: &str) -> Result<(), SignalError>;
+    async fn send_group_typing_stopped(&self, group_id: &str) -> Result<(), SignalError>;
 
     async fn send_read_receipt(&self, recipient: &str, timestamps: Vec<i64>) -> Result<(), SignalError>;
 
     async fn remote_delete(&self, recipient: &str, target_timestamp: i64) -> Result<(), SignalError>;
     async fn remote_delete_group(&self, group_id: &str, target_timestamp: i64) -> Result<(), SignalError>;
 
+    async fn download_attachment(&self, id: &str, recipient: Option<&str>, group_id: Option<&str>) -> Result<Vec<u8>, SignalError>;
+
     fn incoming_messages(&self) -> broadcast::Receiver<IncomingMessage>;
 }
@@ -23,6 +23,21 @@ pub struct Contact {
     pub color: Option<String>,
     #[serde(default)]
     pub blocked: bool,
+    #[serde(default)]
+    pub about: Option<String>,
+    #[serde(default)]
+    pub about_emoji: Option<String>,
+    #[serde(default)]
+    pub profile: Option<ContactProfile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactProfile {
+    #[serde(default)]
+    pub about: Option<String>,
+    #[serde(default)]
+    pub about_emoji: Option<String>,
 }
 
 impl Contact {
@@ -37,6 +52,31 @@ impl Contact {
     pub fn identifier(&self) -> Option<String> {
         self.uuid.clone().or_else(|| self.number.clone())
     }
+
+    pub fn about_line(&self) -> Option<String> {
+        let non_empty = |s: &Option<String>| s.clone().filter(|s| !s.trim().is_empty());
+        let about = non_empty(&self.about)
+            .or_else(|| self.profile.as_ref().and_then(|p| non_empty(&p.about)));
+        let emoji = non_empty(&self.about_emoji)
+            .or_else(|| self.profile.as_ref().and_then(|p| non_empty(&p.about_emoji)));
+        match (emoji, about) {
+            (Some(emoji), Some(about)) => Some(format!("{} {}", emoji, about)),
+            (Some(text), None) | (None, Some(text)) => Some(text),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserStatus {
+    pub recipient: String,
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub is_registered: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,13 +88,32 @@ pub struct Group {
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
-    pub members: Vec<String>,
+    pub members: Vec<GroupMemberRef>,
+    #[serde(default)]
+    pub admins: Vec<GroupMemberRef>,
+    #[serde(default)]
+    pub group_invite_link: Option<String>,
     #[serde(default)]
     pub is_blocked: bool,
     #[serde(default)]
     pub is_member: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMemberRef {
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub uuid: Option<String>,
+}
+
+impl GroupMemberRef {
+    pub fn identifier(&self) -> Option<String> {
+        self.uuid.clone().or_else(|| self.number.clone())
+    }
+}
+
 impl Group {
     pub fn display_name(&self) -> String {
         self.name.clone().unwrap_or_else(|| self.id.clone())
@@ -79,6 +138,8 @@ pub struct Envelope {
     pub source_device: Option<i32>,
     pub timestamp: Option<i64>,
     #[serde(default)]
+    pub server_received_timestamp: Option<i64>,
+    #[serde(default)]
     pub data_message: Option<DataMessage>,
     #[serde(default)]
     pub sync_message: Option<SyncMessage>,
@@ -111,11 +172,25 @@ pub struct DataMessage {
     #[serde(default)]
     pub group_info: Option<GroupInfo>,
     #[serde(default)]
+    pub view_once: bool,
+    #[serde(default)]
     pub attachments: Vec<Attachment>,
     #[serde(default)]
     pub quote: Option<Quote>,
     #[serde(default)]
+    pub previews: Vec<Preview>,
+    #[serde(default)]
+    pub sticker: Option<Sticker>,
+    #[serde(default)]
     pub reaction: Option<Reaction>,
+    #[serde(default)]
+    pub remote_delete: Option<RemoteDelete>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteDelete {
+    pub timestamp: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,6 +225,25 @@ pub struct Quote {
     pub author: Option<String>,
     pub author_uuid: Option<String>,
     pub text: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<QuotedAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotedAttachment {
+    pub content_type: Option<String>,
+    pub filename: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<Attachment>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,6 +257,13 @@ pub struct Reaction {
     pub is_remove: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sticker {
+    pub pack_id: String,
+    pub sticker_id: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncMessage {
@@ -182,9 +283,23 @@ pub struct SentMessage {
     #[serde(default)]
     pub group_info: Option<GroupInfo>,
     #[serde(default)]
+    pub view_once: bool,
+    #[serde(default)]
     pub attachments: Vec<Attachment>,
     #[serde(default)]
     pub edit_message: Option<EditMessage>,
+    #[serde(default)]
+    pub sticker: Option<Sticker>,
+    #[serde(default)]
+    pub quote: Option<Quote>,
+    #[serde(default)]
+    pub previews: Vec<Preview>,
+    #[serde(default)]
+    pub reaction: Option<Reaction>,
+    #[serde(default)]
+    pub remote_delete: Option<RemoteDelete>,
+    #[serde(default)]
+    pub expires_in_seconds: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -229,6 +344,41 @@ pub struct SendMessageParams {
     pub quote_author: Option<String>,
 }
 
+impl SendMessageParams {
+    pub fn direct(recipient: &str, message: &str) -> Self {
+        Self {
+            recipient: Some(vec![recipient.to_string()]),
+            group_id: None,
+            message: message.to_string(),
+            attachments: None,
+            quote_timestamp: None,
+            quote_author: None,
+        }
+    }
+
+    pub fn group(group_id: &str, message: &str) -> Self {
+        Self {
+            recipient: None,
+            group_id: Some(group_id.to_string()),
+            message: message.to_string(),
+            attachments: None,
+            quote_timestamp: None,
+            quote_author: None,
+        }
+    }
+
+    pub fn with_attachments(mut self, attachments: Vec<String>) -> Self {
+        self.attachments = (!attachments.is_empty()).then_some(attachments);
+        self
+    }
+
+    pub fn with_quote(mut self, timestamp: i64, author: &str) -> Self {
+        self.quote_timestamp = Some(timestamp);
+        self.quote_author = Some(author.to_string());
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendResult {
@@ -246,6 +396,34 @@ pub struct SendResultItem {
     pub result_type: Option<String>,
 }
 
+impl SendResultItem {
+    pub fn is_success(&self) -> bool {
+        self.result_type.as_deref() == Some("SUCCESS")
+    }
+
+    pub fn recipient(&self) -> Option<&str> {
+        let address = self.recipient_address.as_ref()?;
+        address.uuid.as_deref().or(address.number.as_deref())
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.recipient_address
+            .iter()
+            .flat_map(|address| [address.uuid.as_deref(), address.number.as_deref()])
+            .flatten()
+    }
+
+    pub fn failure_label(&self) -> &'static str {
+        match self.result_type.as_deref() {
+            Some("UNREGISTERED_FAILURE") => "unregistered",
+            Some("IDENTITY_FAILURE") => "safety number changed",
+            Some("NETWORK_FAILURE") => "network error",
+            Some("RATE_LIMIT_FAILURE") => "rate limited",
+            _ => "failed",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecipientAddress {
@@ -260,6 +438,14 @@ pub struct Identity {
     pub uuid: Option<String>,
     pub fingerprint: Option<String>,
     pub safety_number: Option<String>,
+    #[serde(default)]
+    pub scannable_safety_number: Option<String>,
     pub trust_level: Option<String>,
     pub added_date: Option<i64>,
 }
+
+impl Identity {
+    pub fn is_untrusted(&self) -> bool {
+        self.trust_level.as_deref() == Some("UNTRUSTED")
+    }
+}
@@ -0,0 +1,332 @@
+use super::{Transport, TransportError};
+use crate::infrastructure::jsonrpc::error_codes;
+use async_trait::async_trait;
+use base64::prelude::*;
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, broadcast};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use zbus::zvariant::{DynamicType, OwnedValue};
+use zbus::{Connection, MatchRule, MessageStream};
+
+const BUS_NAME: &str = "org.asamk.Signal";
+const INTERFACE: &str = "org.asamk.Signal";
+
+type MessageReceived = (i64, String, Vec<u8>, String, HashMap<String, OwnedValue>);
+type SyncMessageReceived = (i64, String, String, Vec<u8>, String, HashMap<String, OwnedValue>);
+type ReceiptReceived = (i64, String, String, HashMap<String, OwnedValue>);
+
+pub struct DbusTransport {
+    account: Option<String>,
+    system_bus: bool,
+    connection: Arc<Mutex<Option<Connection>>>,
+    sender: broadcast::Sender<Vec<u8>>,
+    connected: Arc<AtomicBool>,
+    reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DbusTransport {
+    pub fn new(account: Option<String>, system_bus: bool) -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Self {
+            account,
+            system_bus,
+            connection: Arc::new(Mutex::new(None)),
+            sender,
+            connected: Arc::new(AtomicBool::new(false)),
+            reader: Mutex::new(None),
+        }
+    }
+
+    fn object_path(&self) -> String {
+        match &self.account {
+            Some(account) => format!("/org/asamk/Signal/_{}", account.trim_start_matches('+')),
+            None => "/org/asamk/Signal".to_string(),
+        }
+    }
+
+    fn spawn_signal_reader(&self, stream: MessageStream) -> JoinHandle<()> {
+        let sender = self.sender.clone();
+        let connected = self.connected.clone();
+        let account = self.account.clone();
+
+        tokio::spawn(async move {
+            let mut stream = stream;
+            while let Some(msg) = stream.next().await {
+                let msg = match msg {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        warn!("D-Bus signal error: {}", e);
+                        continue;
+                    }
+                };
+                let header = msg.header();
+                let Some(member) = header.member() else {
+                    continue;
+                };
+                let body = msg.body();
+                let envelope = match member.as_str() {
+                    "MessageReceivedV2" => body.deserialize::<MessageReceived>().ok().map(
+                        |(timestamp, sender, group_id, message, _)| {
+                            json!({
+                                "source": sender,
+                                "timestamp": timestamp,
+                                "dataMessage": {
+                                    "timestamp": timestamp,
+                                    "message": message,
+                                    "groupInfo": group_info(&group_id),
+                                },
+                            })
+                        },
+                    ),
+                    "SyncMessageReceivedV2" => body.deserialize::<SyncMessageReceived>().ok().map(
+                        |(timestamp, source, destination, group_id, message, _)| {
+                            json!({
+                                "source": source,
+                                "timestamp": timestamp,
+                                "syncMessage": {
+                                    "sentMessage": {
+                                        "destination": (!destination.is_empty()).then_some(destination),
+                                        "timestamp": timestamp,
+                                        "message": message,
+                                        "groupInfo": group_info(&group_id),
+                                    },
+                                },
+                            })
+                        },
+                    ),
+                    "ReceiptReceivedV2" => body.deserialize::<ReceiptReceived>().ok().map(
+                        |(timestamp, sender, receipt_type, _)| {
+                            json!({
+                                "source": sender,
+                                "receiptMessage": {
+                                    "type": receipt_type.to_uppercase(),
+                                    "timestamps": [timestamp],
+                                },
+                            })
+                        },
+                    ),
+                    other => {
+                        debug!("Ignoring D-Bus signal: {}", other);
+                        None
+                    }
+                };
+                if let Some(envelope) = envelope {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "receive",
+                        "params": { "envelope": envelope, "account": account },
+                    });
+                    if sender.send(notification.to_string().into_bytes()).is_err() {}
+                }
+            }
+            info!("D-Bus signal stream ended");
+            connected.store(false, Ordering::SeqCst);
+        })
+    }
+}
+
+fn group_info(group_id: &[u8]) -> Value {
+    if group_id.is_empty() {
+        Value::Null
+    } else {
+        json!({ "groupId": BASE64_STANDARD.encode(group_id) })
+    }
+}
+
+fn decode_group_id(params: &Value) -> Option<Vec<u8>> {
+    params["groupId"]
+        .as_str()
+        .and_then(|id| BASE64_STANDARD.decode(id).ok())
+}
+
+fn first_recipient(params: &Value) -> String {
+    match &params["recipient"] {
+        Value::Array(recipients) => recipients.first().and_then(Value::as_str),
+        other => other.as_str(),
+    }
+    .unwrap_or_default()
+    .to_string()
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+async fn call<B>(connection: &Connection, path: &str, method: &str, body: &B) -> Result<zbus::Message, zbus::Error>
+where
+    B: serde::Serialize + DynamicType,
+{
+    connection
+        .call_method(Some(BUS_NAME), path, Some(INTERFACE), method, body)
+        .await
+}
+
+async fn dispatch(
+    connection: &Connection,
+    path: &str,
+    method: &str,
+    params: &Value,
+) -> Result<Value, zbus::Error> {
+    match method {
+        "version" => {
+            let reply = call(connection, path, "version", &()).await?;
+            let version: String = reply.body().deserialize()?;
+            Ok(json!({ "version": version }))
+        }
+        "send" => {
+            let message = params["message"].as_str().unwrap_or_default().to_string();
+            let attachments = string_list(&params["attachments"]);
+            let reply = match decode_group_id(params) {
+                Some(group_id) => call(connection, path, "sendGroupMessage", &(message, attachments, group_id)).await?,
+                None => {
+                    let recipients = string_list(&params["recipient"]);
+                    call(connection, path, "sendMessage", &(message, attachments, recipients)).await?
+                }
+            };
+            let timestamp: i64 = reply.body().deserialize()?;
+            Ok(json!({ "timestamp": timestamp, "results": [] }))
+        }
+        "sendTyping" => {
+            let stop = params["stop"].as_bool().unwrap_or(false);
+            match decode_group_id(params) {
+                Some(group_id) => call(connection, path, "sendGroupTyping", &(group_id, stop)).await?,
+                None => call(connection, path, "sendTyping", &(first_recipient(params), stop)).await?,
+            };
+            Ok(json!({}))
+        }
+        "sendReceipt" => {
+            let timestamps: Vec<i64> = params["targetTimestamp"]
+                .as_array()
+                .map(|items| items.iter().filter_map(Value::as_i64).collect())
+                .unwrap_or_default();
+            call(connection, path, "sendReadReceipt", &(first_recipient(params), timestamps)).await?;
+            Ok(json!({}))
+        }
+        "sendReaction" => {
+            let emoji = params["emoji"].as_str().unwrap_or_default().to_string();
+            let remove = params["remove"].as_bool().unwrap_or(false);
+            let author = params["targetAuthor"].as_str().unwrap_or_default().to_string();
+            let timestamp = params["targetTimestamp"].as_i64().unwrap_or_default();
+            match decode_group_id(params) {
+                Some(group_id) => {
+                    let body = (emoji, remove, author, timestamp, group_id);
+                    call(connection, path, "sendGroupMessageReaction", &body).await?
+                }
+                None => {
+                    let body = (emoji, remove, author, timestamp, first_recipient(params));
+                    call(connection, path, "sendMessageReaction", &body).await?
+                }
+            };
+            Ok(json!({}))
+        }
+        "remoteDelete" => {
+            let timestamp = params["targetTimestamp"].as_i64().unwrap_or_default();
+            match decode_group_id(params) {
+                Some(group_id) => call(connection, path, "sendGroupRemoteDeleteMessage", &(timestamp, group_id)).await?,
+                None => call(connection, path, "sendRemoteDeleteMessage", &(timestamp, first_recipient(params))).await?,
+            };
+            Ok(json!({}))
+        }
+        _ => Err(zbus::Error::Unsupported),
+    }
+}
+
+#[async_trait]
+impl Transport for DbusTransport {
+    async fn connect(&self) -> Result<(), TransportError> {
+        let connection = if self.system_bus {
+            Connection::system().await
+        } else {
+            Connection::session().await
+        }
+        .map_err(|e| TransportError::ConnectionFailed(format!("Failed to connect to D-Bus: {}", e)))?;
+
+        let rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface(INTERFACE)
+            .and_then(|b| b.path(self.object_path()))
+            .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?
+            .build();
+        let stream = MessageStream::for_match_rule(rule, &connection, None)
+            .await
+            .map_err(|e| TransportError::ConnectionFailed(format!("Failed to subscribe to signal-cli: {}", e)))?;
+
+        *self.connection.lock().await = Some(connection);
+        self.connected.store(true, Ordering::SeqCst);
+        let reader = self.spawn_signal_reader(stream);
+        if let Some(previous) = self.reader.lock().await.replace(reader) {
+            previous.abort();
+        }
+        info!("Attached to signal-cli over D-Bus at {}", self.object_path());
+        Ok(())
+    }
+
+    async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+        let request: Value =
+            serde_json::from_slice(data).map_err(|e| TransportError::SendFailed(e.to_string()))?;
+        let connection = self
+            .connection
+            .lock()
+            .await
+            .clone()
+            .ok_or(TransportError::ConnectionClosed)?;
+        let path = self.object_path();
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            let id = request["id"].clone();
+            let method = request["method"].as_str().unwrap_or_default();
+            let response = match dispatch(&connection, &path, method, &request["params"]).await {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(zbus::Error::Unsupported) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": error_codes::METHOD_NOT_FOUND,
+                        "message": format!("{} is not supported over D-Bus", method),
+                    },
+                }),
+                Err(e) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -1, "message": e.to_string() },
+                }),
+            };
+            if sender.send(response.to_string().into_bytes()).is_err() {}
+        });
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Vec<u8>, TransportError> {
+        let mut rx = self.sender.subscribe();
+        rx.recv()
+            .await
+            .map_err(|_| TransportError::ConnectionClosed)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Vec<u8>> {
+        self.sender.subscribe()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn disconnect(&self) -> Result<(), TransportError> {
+        if let Some(reader) = self.reader.lock().await.take() {
+            reader.abort();
+        }
+        *self.connection.lock().await = None;
+        self.connected.store(false, Ordering::SeqCst);
+        info!("Detached from signal-cli D-Bus service");
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
+#[cfg(feature = "dbus")]
+mod dbus;
+#[cfg(feature = "rest")]
+mod rest;
 mod stdio;
 
-pub use stdio::StdioTransport;
+#[cfg(feature = "dbus")]
+pub use dbus::DbusTransport;
+#[cfg(feature = "rest")]
+pub use rest::RestTransport;
+pub use stdio::{DEFAULT_MAX_LINE_BYTES, StdioTransport};
 
 use async_trait::async_trait;
 use thiserror::Error;
@@ -33,3 +41,30 @@ pub trait Transport: Send + Sync {
     fn is_connected(&self) -> bool;
     async fn disconnect(&self) -> Result<(), TransportError>;
 }
+
+#[async_trait]
+impl Transport for Box<dyn Transport> {
+    async fn connect(&self) -> Result<(), TransportError> {
+        (**self).connect().await
+    }
+
+    async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+        (**self).send(data).await
+    }
+
+    async fn receive(&self) -> Result<Vec<u8>, TransportError> {
+        (**self).receive().await
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Vec<u8>> {
+        (**self).subscribe()
+    }
+
+    fn is_connected(&self) -> bool {
+        (**self).is_connected()
+    }
+
+    async fn disconnect(&self) -> Result<(), TransportError> {
+        (**self).disconnect().await
+    }
+}
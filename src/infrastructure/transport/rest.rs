@@ -0,0 +1,294 @@
+use super::{Transport, TransportError};
+use crate::infrastructure::jsonrpc::error_codes;
+use async_trait::async_trait;
+use base64::prelude::*;
+use futures_util::StreamExt;
+use reqwest::Method;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, broadcast};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{debug, info, warn};
+
+pub struct RestTransport {
+    base_url: String,
+    account: Option<String>,
+    http: reqwest::Client,
+    sender: broadcast::Sender<Vec<u8>>,
+    connected: Arc<AtomicBool>,
+    reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+enum RestError {
+    Unsupported,
+    Failed(String),
+}
+
+impl RestTransport {
+    pub fn new(base_url: &str, account: Option<String>) -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            account,
+            http: reqwest::Client::new(),
+            sender,
+            connected: Arc::new(AtomicBool::new(false)),
+            reader: Mutex::new(None),
+        }
+    }
+
+    fn receive_url(&self, account: &str) -> String {
+        let ws_base = match self.base_url.split_once("://") {
+            Some(("https", rest)) => format!("wss://{}", rest),
+            Some((_, rest)) => format!("ws://{}", rest),
+            None => format!("ws://{}", self.base_url),
+        };
+        format!("{}/v1/receive/{}", ws_base, account)
+    }
+}
+
+fn rest_recipient(params: &Value) -> String {
+    if let Some(group_id) = params["groupId"].as_str() {
+        return format!("group.{}", BASE64_STANDARD.encode(group_id));
+    }
+    match &params["recipient"] {
+        Value::Array(recipients) => recipients.first().and_then(Value::as_str),
+        other => other.as_str(),
+    }
+    .unwrap_or_default()
+    .to_string()
+}
+
+fn encode_attachments(params: &Value) -> Result<Vec<String>, RestError> {
+    let Some(paths) = params["attachments"].as_array() else {
+        return Ok(Vec::new());
+    };
+    paths
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|path| {
+            std::fs::read(path)
+                .map(|data| BASE64_STANDARD.encode(data))
+                .map_err(|e| RestError::Failed(format!("Failed to read {}: {}", path, e)))
+        })
+        .collect()
+}
+
+async fn request(
+    http: &reqwest::Client,
+    method: Method,
+    url: String,
+    body: Option<Value>,
+) -> Result<Value, RestError> {
+    let mut builder = http.request(method, url);
+    if let Some(body) = body {
+        builder = builder.json(&body);
+    }
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| RestError::Failed(e.to_string()))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| RestError::Failed(e.to_string()))?;
+    let value = serde_json::from_str(&text).unwrap_or(Value::Null);
+    if !status.is_success() {
+        let message = value["error"].as_str().map(String::from).unwrap_or(text);
+        return Err(RestError::Failed(message));
+    }
+    Ok(value)
+}
+
+async fn dispatch(
+    http: &reqwest::Client,
+    base_url: &str,
+    account: &str,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RestError> {
+    match method {
+        "version" => {
+            let about = request(http, Method::GET, format!("{}/v1/about", base_url), None).await?;
+            Ok(json!({ "version": about["version"] }))
+        }
+        "send" => {
+            let recipients = match params["groupId"].as_str() {
+                Some(_) => vec![rest_recipient(params)],
+                None => params["recipient"]
+                    .as_array()
+                    .map(|items| items.iter().filter_map(Value::as_str).map(String::from).collect())
+                    .unwrap_or_default(),
+            };
+            let mut body = json!({
+                "number": account,
+                "recipients": recipients,
+                "message": params["message"],
+                "base64_attachments": encode_attachments(params)?,
+            });
+            if let Some(timestamp) = params["quoteTimestamp"].as_i64() {
+                body["quote_timestamp"] = json!(timestamp);
+                body["quote_author"] = params["quoteAuthor"].clone();
+            }
+            let result = request(http, Method::POST, format!("{}/v2/send", base_url), Some(body)).await?;
+            let timestamp = match &result["timestamp"] {
+                Value::String(s) => s.parse::<i64>().ok(),
+                other => other.as_i64(),
+            };
+            Ok(json!({ "timestamp": timestamp, "results": [] }))
+        }
+        "sendTyping" => {
+            let method = if params["stop"].as_bool().unwrap_or(false) {
+                Method::DELETE
+            } else {
+                Method::PUT
+            };
+            let url = format!("{}/v1/typing-indicator/{}", base_url, account);
+            request(http, method, url, Some(json!({ "recipient": rest_recipient(params) }))).await?;
+            Ok(json!({}))
+        }
+        "sendReceipt" => {
+            let url = format!("{}/v1/receipts/{}", base_url, account);
+            for timestamp in params["targetTimestamp"].as_array().into_iter().flatten() {
+                let body = json!({
+                    "receipt_type": "read",
+                    "recipient": rest_recipient(params),
+                    "timestamp": timestamp,
+                });
+                request(http, Method::POST, url.clone(), Some(body)).await?;
+            }
+            Ok(json!({}))
+        }
+        "sendReaction" => {
+            let method = if params["remove"].as_bool().unwrap_or(false) {
+                Method::DELETE
+            } else {
+                Method::POST
+            };
+            let body = json!({
+                "reaction": params["emoji"],
+                "recipient": rest_recipient(params),
+                "target_author": params["targetAuthor"],
+                "timestamp": params["targetTimestamp"],
+            });
+            request(http, method, format!("{}/v1/reactions/{}", base_url, account), Some(body)).await?;
+            Ok(json!({}))
+        }
+        "remoteDelete" => {
+            let body = json!({
+                "recipient": rest_recipient(params),
+                "timestamp": params["targetTimestamp"],
+            });
+            let url = format!("{}/v1/remote-delete/{}", base_url, account);
+            request(http, Method::DELETE, url, Some(body)).await?;
+            Ok(json!({}))
+        }
+        _ => Err(RestError::Unsupported),
+    }
+}
+
+#[async_trait]
+impl Transport for RestTransport {
+    async fn connect(&self) -> Result<(), TransportError> {
+        let account = self.account.as_deref().ok_or_else(|| {
+            TransportError::ConnectionFailed("The REST transport needs an account (-a)".into())
+        })?;
+        let (stream, _) = tokio_tungstenite::connect_async(self.receive_url(account))
+            .await
+            .map_err(|e| TransportError::ConnectionFailed(format!("Failed to open receive socket: {}", e)))?;
+
+        let sender = self.sender.clone();
+        let connected = self.connected.clone();
+        let reader = tokio::spawn(async move {
+            let (_, mut incoming) = stream.split();
+            while let Some(frame) = incoming.next().await {
+                let text = match frame {
+                    Ok(WsMessage::Text(text)) => text,
+                    Ok(WsMessage::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        warn!("Receive socket error: {}", e);
+                        break;
+                    }
+                };
+                let Ok(params) = serde_json::from_str::<Value>(text.as_str()) else {
+                    debug!("Ignoring non-JSON frame: {}", text.as_str());
+                    continue;
+                };
+                let notification = json!({ "jsonrpc": "2.0", "method": "receive", "params": params });
+                if sender.send(notification.to_string().into_bytes()).is_err() {}
+            }
+            info!("REST receive socket closed");
+            connected.store(false, Ordering::SeqCst);
+        });
+
+        if let Some(previous) = self.reader.lock().await.replace(reader) {
+            previous.abort();
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        info!("Connected to signal-cli-rest-api at {}", self.base_url);
+        Ok(())
+    }
+
+    async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+        if !self.is_connected() {
+            return Err(TransportError::ConnectionClosed);
+        }
+        let request: Value =
+            serde_json::from_slice(data).map_err(|e| TransportError::SendFailed(e.to_string()))?;
+        let http = self.http.clone();
+        let base_url = self.base_url.clone();
+        let account = self.account.clone().unwrap_or_default();
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            let id = request["id"].clone();
+            let method = request["method"].as_str().unwrap_or_default();
+            let response = match dispatch(&http, &base_url, &account, method, &request["params"]).await {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(RestError::Unsupported) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": error_codes::METHOD_NOT_FOUND,
+                        "message": format!("{} is not supported by the REST transport", method),
+                    },
+                }),
+                Err(RestError::Failed(message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -1, "message": message },
+                }),
+            };
+            if sender.send(response.to_string().into_bytes()).is_err() {}
+        });
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Vec<u8>, TransportError> {
+        let mut rx = self.sender.subscribe();
+        rx.recv()
+            .await
+            .map_err(|_| TransportError::ConnectionClosed)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Vec<u8>> {
+        self.sender.subscribe()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn disconnect(&self) -> Result<(), TransportError> {
+        if let Some(reader) = self.reader.lock().await.take() {
+            reader.abort();
+        }
+        self.connected.store(false, Ordering::SeqCst);
+        info!("Disconnected from signal-cli-rest-api");
+        Ok(())
+    }
+}
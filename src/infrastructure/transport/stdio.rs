@@ -3,13 +3,16 @@ use async_trait::async_trait;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::{broadcast, Mutex};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+pub const DEFAULT_MAX_LINE_BYTES: usize = 64 * 1024 * 1024;
 
 pub struct StdioTransport {
     account: Option<String>,
+    max_line_bytes: usize,
     child: Arc<Mutex<Option<Child>>>,
     stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
     sender: broadcast::Sender<Vec<u8>>,
@@ -17,10 +20,11 @@ pub struct StdioTransport {
 }
 
 impl StdioTransport {
-    pub fn new(account: Option<String>) -> Self {
+    pub fn new(account: Option<String>, max_line_bytes: usize) -> Self {
         let (sender, _) = broadcast::channel(256);
         Self {
             account,
+            max_line_bytes,
             child: Arc::new(Mutex::new(None)),
             stdin: Arc::new(Mutex::new(None)),
             sender,
@@ -32,16 +36,20 @@ impl StdioTransport {
         stdout: tokio::process::ChildStdout,
         sender: broadcast::Sender<Vec<u8>>,
         connected: Arc<AtomicBool>,
+        max_line_bytes: usize,
     ) {
         tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            let mut reader = BufReader::new(stdout);
+            let mut line = Vec::new();
 
             loop {
-                match lines.next_line().await {
-                    Ok(Some(line)) => {
-                        debug!("Received: {}", line);
-                        if sender.send(line.into_bytes()).is_err() {}
+                match read_bounded_line(&mut reader, &mut line, max_line_bytes).await {
+                    Ok(Some(true)) => {
+                        debug!("Received: {}", String::from_utf8_lossy(&line));
+                        if sender.send(std::mem::take(&mut line)).is_err() {}
+                    }
+                    Ok(Some(false)) => {
+                        warn!("Skipped line longer than {} bytes from signal-cli", max_line_bytes);
                     }
                     Ok(None) => {
                         info!("signal-cli process ended");
@@ -100,7 +108,7 @@ impl Transport for StdioTransport {
         }
 
         let connected = Arc::new(AtomicBool::new(true));
-        Self::spawn_reader(stdout, self.sender.clone(), connected.clone());
+        Self::spawn_reader(stdout, self.sender.clone(), connected.clone(), self.max_line_bytes);
 
         self.connected.store(true, Ordering::SeqCst);
         info!("signal-cli jsonRpc started successfully");
@@ -163,6 +171,87 @@ impl Transport for StdioTransport {
     }
 }
 
+async fn read_bounded_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+    max_bytes: usize,
+) -> std::io::Result<Option<bool>> {
+    line.clear();
+    let mut overflow = false;
+    let mut read_any = false;
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(read_any.then_some(!overflow));
+        }
+        read_any = true;
+
+        let newline = available.iter().position(|&b| b == b'\n');
+        let chunk = &available[..newline.unwrap_or(available.len())];
+        if !overflow {
+            if line.len() + chunk.len() > max_bytes {
+                overflow = true;
+                line.clear();
+                line.shrink_to_fit();
+            } else {
+                line.extend_from_slice(chunk);
+            }
+        }
+
+        let consumed = newline.map_or(available.len(), |pos| pos + 1);
+        reader.consume(consumed);
+
+        if newline.is_some() {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Ok(Some(!overflow));
+        }
+    }
+}
+
 impl Drop for StdioTransport {
     fn drop(&mut self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn skips_an_oversized_line_and_keeps_reading() {
+        let huge = "A".repeat(4 * 1024 * 1024);
+        let input = format!("{{\"id\":1}}\n{}\n{{\"id\":2}}\r\n", huge);
+        let mut reader = BufReader::with_capacity(64 * 1024, input.as_bytes());
+        let mut line = Vec::new();
+        let max = 1024 * 1024;
+
+        assert_eq!(read_bounded_line(&mut reader, &mut line, max).await.unwrap(), Some(true));
+        assert_eq!(line, b"{\"id\":1}");
+        assert_eq!(read_bounded_line(&mut reader, &mut line, max).await.unwrap(), Some(false));
+        assert!(line.is_empty());
+        assert_eq!(read_bounded_line(&mut reader, &mut line, max).await.unwrap(), Some(true));
+        assert_eq!(line, b"{\"id\":2}");
+        assert_eq!(read_bounded_line(&mut reader, &mut line, max).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn accepts_a_line_exactly_at_the_cap() {
+        let input = format!("{}\n", "B".repeat(16));
+        let mut reader = input.as_bytes();
+        let mut line = Vec::new();
+
+        assert_eq!(read_bounded_line(&mut reader, &mut line, 16).await.unwrap(), Some(true));
+        assert_eq!(line.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn reports_a_final_line_without_a_newline() {
+        let mut reader = "tail".as_bytes();
+        let mut line = Vec::new();
+
+        assert_eq!(read_bounded_line(&mut reader, &mut line, 16).await.unwrap(), Some(true));
+        assert_eq!(line, b"tail");
+    }
+}
@@ -5,21 +5,35 @@ mod image_cache;
 mod infrastructure;
 mod storage;
 mod ui;
+mod util;
 
-use app::{App, RemoteDeleteTarget, SendTarget};
+use app::{
+    App, ConnectionHealth, HEARTBEAT_INTERVAL, ListDensity, NotificationPrivacy, RemoteDeleteTarget, SPINNER_INTERVAL, SendTarget,
+    remove_view_once_copies, resolve_attachment_path,
+};
 use avatar::AvatarManager;
 use crossterm::ExecutableCommand;
 use crossterm::cursor;
-use crossterm::event::{self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event};
-use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+    EnableMouseCapture, Event, EventStream, MouseEventKind,
+};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
+use futures_util::{FutureExt, StreamExt};
 use image_cache::ImageCache;
-use infrastructure::{SignalClient, SignalRepository};
+use infrastructure::{
+    Contact, DEFAULT_MAX_LINE_BYTES, Group, IncomingMessage, RpcTimeouts, SendMessageParams, SendResult, SendResultItem, SignalClient, SignalError,
+    SignalRepository, UserStatus,
+};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
-use std::io::stdout;
+use ratatui_image::picker::Picker;
+use std::io::{Write, stdout};
 use std::sync::Arc;
-use std::time::Duration;
-use storage::{Message, MessageContent, SqliteStorage, StorageRepository};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use storage::{DeliveryState, DeliveryStatus, Message, MessageContent, SqliteStorage, StorageRepository};
+use util::{DEFAULT_OTHER_DAY_FORMAT, DEFAULT_TODAY_FORMAT, TimestampFormat, is_valid_time_format, now_millis};
 
 fn parse_account() -> Option<String> {
     let args: Vec<String> = std::env::args().collect();
@@ -33,7 +47,202 @@ fn parse_account() -> Option<String> {
     None
 }
 
+fn parse_timeout_secs(flag: &str) -> Option<Duration> {
+    let args: Vec<String> = std::env::args().collect();
+    let pos = args.iter().position(|a| a == flag)?;
+    let secs = args.get(pos + 1)?.parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+fn parse_directory_refresh() -> Option<Duration> {
+    match parse_timeout_secs("--refresh-interval") {
+        Some(interval) if interval.is_zero() => None,
+        Some(interval) => Some(interval),
+        None => Some(DEFAULT_DIRECTORY_REFRESH),
+    }
+}
+
+fn spawn_directory_refresh(app: &App, tasks: &mpsc::UnboundedSender<TaskResult>) {
+    let signal = app.signal.clone();
+    let storage = app.storage.clone();
+    let tasks = tasks.clone();
+    tokio::spawn(async move {
+        if let (Ok(contacts), Ok(groups)) = tokio::join!(signal.list_contacts(), signal.list_groups()) {
+            let _ = tasks.send(TaskResult::Directory { storage, contacts, groups });
+        }
+    });
+}
+
+fn parse_max_width() -> Option<u16> {
+    let args: Vec<String> = std::env::args().collect();
+    let pos = args.iter().position(|a| a == "--max-width")?;
+    args.get(pos + 1)?.parse().ok().filter(|&width| width >= 20)
+}
+
+fn parse_preview_length() -> Option<usize> {
+    let args: Vec<String> = std::env::args().collect();
+    let pos = args.iter().position(|a| a == "--preview-length")?;
+    args.get(pos + 1)?.parse().ok().filter(|&len| len > 0)
+}
+
+fn parse_timestamp_format() -> anyhow::Result<TimestampFormat> {
+    let args: Vec<String> = std::env::args().collect();
+    let value_of = |flag: &str| {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|pos| args.get(pos + 1).cloned())
+    };
+    let today = value_of("--time-format");
+    let other_day = value_of("--date-format");
+    if has_flag("--relative-time") {
+        if today.is_some() || other_day.is_some() {
+            anyhow::bail!("--relative-time cannot be combined with --time-format or --date-format");
+        }
+        return Ok(TimestampFormat::Relative);
+    }
+    let today = today.unwrap_or_else(|| DEFAULT_TODAY_FORMAT.to_string());
+    let other_day = other_day.unwrap_or_else(|| DEFAULT_OTHER_DAY_FORMAT.to_string());
+    for format in [&today, &other_day] {
+        if !is_valid_time_format(format) {
+            anyhow::bail!("Invalid time format: {}", format);
+        }
+    }
+    Ok(TimestampFormat::Absolute { today, other_day })
+}
+
+fn parse_max_line_bytes() -> usize {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--max-line-mb")
+        .and_then(|pos| args.get(pos + 1)?.parse::<usize>().ok())
+        .filter(|&mb| mb > 0)
+        .map_or(DEFAULT_MAX_LINE_BYTES, |mb| mb * 1024 * 1024)
+}
+
+fn parse_rpc_timeouts() -> RpcTimeouts {
+    let defaults = RpcTimeouts::default();
+    let default = parse_timeout_secs("--rpc-timeout").unwrap_or(defaults.default);
+    let attachments = parse_timeout_secs("--attachment-timeout")
+        .unwrap_or(defaults.attachments)
+        .max(default);
+    RpcTimeouts { default, attachments }
+}
+
+fn create_signal_client(account: Option<String>) -> anyhow::Result<SignalClient> {
+    let timeouts = parse_rpc_timeouts();
+    if has_flag("--dbus") || has_flag("--dbus-system") {
+        #[cfg(feature = "dbus")]
+        {
+            let transport = infrastructure::DbusTransport::new(account.clone(), has_flag("--dbus-system"));
+            return Ok(SignalClient::with_transport(transport, account, timeouts));
+        }
+        #[cfg(not(feature = "dbus"))]
+        anyhow::bail!("D-Bus support is not compiled in; rebuild with --features dbus");
+    }
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(url) = args.iter().position(|a| a == "--rest-url").and_then(|pos| args.get(pos + 1)) {
+        #[cfg(feature = "rest")]
+        {
+            let transport = infrastructure::RestTransport::new(url, account.clone());
+            return Ok(SignalClient::with_transport(transport, account, timeouts));
+        }
+        #[cfg(not(feature = "rest"))]
+        anyhow::bail!("REST support is not compiled in; rebuild with --features rest to use {}", url);
+    }
+    Ok(SignalClient::new(account, timeouts, parse_max_line_bytes()))
+}
+
+fn parse_audio_player() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--audio-player")
+        .and_then(|pos| args.get(pos + 1).cloned())
+}
+
+fn parse_passphrase() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--passphrase")
+        .and_then(|pos| args.get(pos + 1).cloned())
+        .or_else(|| std::env::var("SIGNAL_TTY_PASSPHRASE").ok())
+        .filter(|p| !p.is_empty())
+}
+
+fn parse_notification_privacy() -> anyhow::Result<Option<NotificationPrivacy>> {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(value) = args
+        .iter()
+        .position(|a| a == "--notify-content")
+        .and_then(|pos| args.get(pos + 1))
+    else {
+        return Ok(None);
+    };
+    NotificationPrivacy::parse(value)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("--notify-content must be full, sender or generic"))
+}
+
+fn send_desktop_notification(title: &str, body: &str) {
+    let _ = tokio::process::Command::new("notify-send")
+        .args(["--app-name", "signal-tty", title, body])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
+
+fn window_title(unread: u32) -> String {
+    if unread > 0 {
+        format!("signal-tty ({})", unread)
+    } else {
+        "signal-tty".to_string()
+    }
+}
+
+fn has_flag(flag: &str) -> bool {
+    std::env::args().skip(1).any(|a| a == flag)
+}
+
+fn database_size(db_path: &std::path::Path) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .filter_map(|suffix| {
+            let mut path = db_path.as_os_str().to_owned();
+            path.push(suffix);
+            std::fs::metadata(path).ok()
+        })
+        .map(|m| m.len())
+        .sum()
+}
+
+fn vacuum_database(db_path: &std::path::Path, passphrase: Option<&str>) -> anyhow::Result<()> {
+    let storage = SqliteStorage::open(db_path, passphrase)?;
+    let before = database_size(db_path);
+    storage.vacuum()?;
+    drop(storage);
+    let after = database_size(db_path);
+    println!(
+        "Vacuumed {}: {} KiB -> {} KiB",
+        db_path.display(),
+        before / 1024,
+        after / 1024
+    );
+    Ok(())
+}
+
 fn get_data_dir() -> std::path::PathBuf {
+    let args: Vec<String> = std::env::args().collect();
+    let overridden = args
+        .iter()
+        .position(|a| a == "--data-dir")
+        .and_then(|pos| args.get(pos + 1).cloned())
+        .or_else(|| std::env::var("SIGNAL_TTY_DATA_DIR").ok())
+        .filter(|d| !d.is_empty());
+    if let Some(dir) = overridden {
+        let data_dir = std::path::PathBuf::from(dir);
+        std::fs::create_dir_all(&data_dir).ok();
+        return data_dir;
+    }
     if let Some(proj_dirs) = directories::ProjectDirs::from("com", "signal-tty", "signal-tty") {
         let data_dir = proj_dirs.data_dir();
         std::fs::create_dir_all(data_dir).ok();
@@ -43,11 +252,41 @@ fn get_data_dir() -> std::path::PathBuf {
     }
 }
 
-fn now_millis() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
+fn database_path(account: Option<&str>, passphrase: Option<&str>) -> std::path::PathBuf {
+    let data_dir = get_data_dir();
+    let legacy = data_dir.join("messages.db");
+    let Some(account) = account else {
+        return legacy;
+    };
+    let name: String = account
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '+')
+        .collect();
+    let path = data_dir.join(format!("messages-{}.db", name));
+    if !path.exists() && legacy.exists() && legacy_database_belongs_to(&legacy, account, passphrase) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut from = legacy.as_os_str().to_owned();
+            from.push(suffix);
+            let mut to = path.as_os_str().to_owned();
+            to.push(suffix);
+            let _ = std::fs::rename(from, to);
+        }
+    }
+    path
+}
+
+fn legacy_database_belongs_to(legacy: &std::path::Path, account: &str, passphrase: Option<&str>) -> bool {
+    let stored = SqliteStorage::open(legacy, passphrase)
+        .ok()
+        .and_then(|storage| storage.get_setting("account").ok().flatten());
+    match stored {
+        Some(stored) => stored == account,
+        None => configured_accounts() == [account],
+    }
+}
+
+fn state_path(db_path: &std::path::Path) -> std::path::PathBuf {
+    db_path.with_extension("state.json")
 }
 
 fn mime_from_path(path: &std::path::Path) -> Option<String> {
@@ -69,6 +308,8 @@ fn mime_from_path(path: &std::path::Path) -> Option<String> {
         "wav" => "audio/wav",
         "ogg" => "audio/ogg",
         "flac" => "audio/flac",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
         "pdf" => "application/pdf",
         "txt" => "text/plain",
         "html" | "htm" => "text/html",
@@ -82,34 +323,402 @@ fn mime_from_path(path: &std::path::Path) -> Option<String> {
     Some(mime.to_string())
 }
 
-fn get_my_number() -> Option<String> {
-    let home = std::env::var("HOME").ok()?;
+fn handle_resize(
+    image_cache: &mut Option<ImageCache>,
+    avatar_manager: &mut Option<AvatarManager>,
+    font_size_changed: bool,
+) {
+    if font_size_changed && let Ok(picker) = Picker::from_query_stdio() {
+        if let Some(cache) = image_cache {
+            cache.set_picker(picker.clone());
+        }
+        if let Some(mgr) = avatar_manager {
+            mgr.set_picker(picker);
+        }
+    } else if let Some(cache) = image_cache {
+        cache.invalidate();
+    }
+}
+
+fn add_outgoing_message(
+    app: &mut App,
+    conv_id: &str,
+    text: &str,
+    attachments: &[std::path::PathBuf],
+    quote: Option<storage::Quote>,
+) -> Message {
+    let content = if !attachments.is_empty() {
+        let att_info: Vec<_> = attachments
+            .iter()
+            .map(|p| storage::AttachmentInfo {
+                id: None,
+                content_type: mime_from_path(p),
+                filename: p.file_name().map(|n| n.to_string_lossy().to_string()),
+                size: p.metadata().ok().map(|m| m.len()),
+                local_path: Some(p.to_string_lossy().to_string()),
+                view_once: false,
+                viewed: false,
+            })
+            .collect();
+        MessageContent::Attachment { attachments: att_info }
+    } else {
+        MessageContent::Text { body: text.to_string() }
+    };
+
+    let msg = Message {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: conv_id.to_string(),
+        sender_uuid: app.my_uuid.clone().unwrap_or_default(),
+        sender_name: None,
+        timestamp: now_millis(),
+        server_timestamp: None,
+        received_at: now_millis(),
+        content,
+        quote,
+        is_outgoing: true,
+        is_read: true,
+        is_deleted: false,
+        is_edited: false,
+        link_preview: None,
+    };
+    app.add_message_to_conversation(conv_id, msg.clone());
+    let _ = app.storage.save_message(&msg);
+
+    for att_path in attachments {
+        if mime_from_path(att_path).is_some_and(|m| m.starts_with("image/")) {
+            app.pending_preload_paths.push(att_path.to_string_lossy().to_string());
+        }
+    }
+
+    msg
+}
+
+fn attachment_path_strings(attachments: &[std::path::PathBuf]) -> Vec<String> {
+    attachments
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+fn spawn_download(app: &App, tasks: &mpsc::UnboundedSender<TaskResult>, id: String) {
+    let (recipient, group_id) = match app.attachment_source(&id) {
+        Some(SendTarget::Direct(recipient)) => (Some(recipient), None),
+        Some(SendTarget::Group(group_id)) => (None, Some(group_id)),
+        None => (None, None),
+    };
+    let signal = app.signal.clone();
+    let tasks = tasks.clone();
+    tokio::spawn(async move {
+        let success = download_attachment(&signal, &id, recipient, group_id).await;
+        let _ = tasks.send(TaskResult::Downloaded { id, success });
+    });
+}
+
+fn spawn_link(signal: SignalClient, tasks: &mpsc::UnboundedSender<TaskResult>) {
+    let tasks = tasks.clone();
+    tokio::spawn(async move {
+        let uri = match signal.connect().await {
+            Ok(()) => signal.start_link().await,
+            Err(e) => Err(e),
+        };
+        let uri = match uri {
+            Ok(uri) => uri,
+            Err(e) => {
+                let _ = tasks.send(TaskResult::LinkStarted(Err(e)));
+                return;
+            }
+        };
+        let _ = tasks.send(TaskResult::LinkStarted(Ok(uri.clone())));
+        let result = signal.finish_link(&uri, LINK_DEVICE_NAME).await;
+        let _ = signal.disconnect().await;
+        let _ = tasks.send(TaskResult::Linked(result));
+    });
+}
+
+async fn download_attachment(
+    signal: &SignalClient,
+    id: &str,
+    recipient: Option<String>,
+    group_id: Option<String>,
+) -> bool {
+    let Some(path) = resolve_attachment_path(id) else {
+        return false;
+    };
+    let Ok(data) = signal
+        .download_attachment(id, recipient.as_deref(), group_id.as_deref())
+        .await
+    else {
+        return false;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    std::fs::write(&path, data).is_ok()
+}
+
+enum TaskResult {
+    Sent {
+        storage: Arc<SqliteStorage>,
+        message: Option<Box<Message>>,
+        recipient: String,
+        result: Result<SendResult, SignalError>,
+    },
+    RemoteDeleteFailed(SignalError),
+    Downloaded {
+        id: String,
+        success: bool,
+    },
+    Heartbeat(bool),
+    Reconnected(Result<(), SignalError>),
+    Directory {
+        storage: Arc<SqliteStorage>,
+        contacts: Vec<Contact>,
+        groups: Vec<Group>,
+    },
+    GroupDetails {
+        storage: Arc<SqliteStorage>,
+        result: Result<Group, SignalError>,
+    },
+    UserStatus {
+        storage: Arc<SqliteStorage>,
+        statuses: Vec<UserStatus>,
+    },
+    NumberLookup {
+        storage: Arc<SqliteStorage>,
+        number: String,
+        result: Result<Vec<UserStatus>, SignalError>,
+    },
+    VoiceRecorded(std::path::PathBuf),
+    Unregistered(Result<(), SignalError>),
+    LinkStarted(Result<String, SignalError>),
+    Linked(Result<String, SignalError>),
+}
+
+const SYNC_BATCH_SIZE: usize = 100;
+const LINK_DEVICE_NAME: &str = "signal-tty";
+const DEFAULT_DIRECTORY_REFRESH: Duration = Duration::from_secs(15 * 60);
+const RELATIVE_TIME_REFRESH: Duration = Duration::from_secs(30);
+const RECORDING_REFRESH: Duration = Duration::from_secs(1);
+
+enum Wake {
+    Terminal(Option<std::io::Result<Event>>),
+    Incoming(Result<Box<IncomingMessage>, broadcast::error::RecvError>),
+    Task(TaskResult),
+    ImageLoaded,
+    Timer,
+}
+
+async fn wait_for_loaded_image(image_cache: &Option<ImageCache>) {
+    match image_cache {
+        Some(cache) => cache.wait_for_loaded_image().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn spawn_send(
+    app: &mut App,
+    tasks: &mpsc::UnboundedSender<TaskResult>,
+    target: &SendTarget,
+    text: &str,
+    attachment_paths: Vec<String>,
+    message: Option<Message>,
+) {
+    let mut params = match target {
+        SendTarget::Direct(recipient) => SendMessageParams::direct(recipient, text),
+        SendTarget::Group(group_id) => SendMessageParams::group(group_id, text),
+    }
+    .with_attachments(attachment_paths);
+    if let Some(quote) = message.as_ref().and_then(|m| m.quote.as_ref()) {
+        params = params.with_quote(quote.timestamp, &quote.author_uuid);
+    }
+    let recipient = target.recipient().to_string();
+    if let Some(msg) = &message {
+        app.record_delivery(msg, &recipient, DeliveryState::Sending);
+    }
+
+    let signal = app.signal.clone();
+    let storage = app.storage.clone();
+    let tasks = tasks.clone();
+    tokio::spawn(async move {
+        let result = signal.send(params).await;
+        let message = message.map(Box::new);
+        let _ = tasks.send(TaskResult::Sent { storage, message, recipient, result });
+    });
+}
+
+fn apply_send_result(
+    app: &mut App,
+    storage: &Arc<SqliteStorage>,
+    message: Option<Message>,
+    recipient: &str,
+    result: Result<SendResult, SignalError>,
+) {
+    let Some(mut msg) = message else {
+        if let Err(e) = &result {
+            app.status_message = Some(format!("Send failed: {}", e));
+        }
+        return;
+    };
+
+    let mut statuses = Vec::new();
+    let state = match result {
+        Ok(send_result) => {
+            if let Some(ts) = send_result.timestamp {
+                msg.timestamp = ts;
+            }
+            let target = app.canonical_recipient(recipient);
+            for item in &send_result.results {
+                if item.addresses().any(|id| app.canonical_recipient(id) == target) {
+                    continue;
+                }
+                if let Some(member) = item.recipient() {
+                    let state = if item.is_success() { DeliveryState::Sent } else { DeliveryState::Failed };
+                    statuses.push((member.to_string(), state));
+                }
+            }
+            let delivered = send_result.results.iter().filter(|i| i.is_success()).count();
+            if delivered < send_result.results.len() {
+                let name = app
+                    .conversations
+                    .iter()
+                    .find(|c| c.conversation.id == msg.conversation_id)
+                    .map(|c| c.conversation.display_name())
+                    .unwrap_or_default();
+                app.status_message = Some(format!(
+                    "Delivered to {}/{} in {}; {}",
+                    delivered,
+                    send_result.results.len(),
+                    name,
+                    summarize_send_failures(&send_result.results)
+                ));
+            }
+            if delivered == 0 && !send_result.results.is_empty() {
+                DeliveryState::Failed
+            } else {
+                DeliveryState::Sent
+            }
+        }
+        Err(e) => {
+            if matches!(e, SignalError::UntrustedIdentity { .. }) {
+                app.pending_identity_refresh = true;
+            }
+            app.status_message = Some(format!("Send failed: {}", e));
+            DeliveryState::Failed
+        }
+    };
+    statuses.push((recipient.to_string(), state));
+
+    if !Arc::ptr_eq(storage, &app.storage) {
+        let _ = storage.save_message(&msg);
+        for (recipient, state) in statuses {
+            let _ = storage.save_delivery_status(&DeliveryStatus {
+                message_id: msg.id.clone(),
+                recipient_uuid: recipient,
+                state,
+                updated_at: now_millis(),
+            });
+        }
+        return;
+    }
+
+    app.reconcile_sent_message(&msg);
+    for (recipient, state) in statuses {
+        app.record_delivery(&msg, &recipient, state);
+    }
+}
+
+fn summarize_send_failures(results: &[SendResultItem]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for item in results.iter().filter(|i| !i.is_success()) {
+        let label = item.failure_label();
+        match counts.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((label, 1)),
+        }
+    }
+    counts
+        .iter()
+        .map(|(label, count)| format!("{} {}", count, label))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn configured_accounts() -> Vec<String> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Vec::new();
+    };
     let accounts_path =
         std::path::PathBuf::from(home).join(".local/share/signal-cli/data/accounts.json");
-    let data = std::fs::read_to_string(accounts_path).ok()?;
-    let json: serde_json::Value = serde_json::from_str(&data).ok()?;
-    json["accounts"].get(0)?["number"]
-        .as_str()
-        .map(String::from)
+    let Some(json) = std::fs::read_to_string(accounts_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+    else {
+        return Vec::new();
+    };
+    json["accounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|account| account["number"].as_str().map(String::from))
+        .collect()
+}
+
+fn get_my_number() -> Option<String> {
+    configured_accounts().into_iter().next()
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let account = parse_account();
     let my_number = account.clone().or_else(get_my_number);
-    let db_path = get_data_dir().join("messages.db");
-    let storage = Arc::new(SqliteStorage::open(&db_path)?);
-    let signal = SignalClient::new(account);
+    let passphrase = parse_passphrase();
+    let db_path = database_path(my_number.as_deref(), passphrase.as_deref());
+    let timestamp_format = parse_timestamp_format()?;
+    if has_flag("--vacuum") {
+        return vacuum_database(&db_path, passphrase.as_deref());
+    }
+    let storage = Arc::new(SqliteStorage::open(&db_path, passphrase.as_deref())?);
+    let signal = create_signal_client(account)?;
 
     signal.connect().await?;
     let mut messages = signal.incoming_messages();
 
-    let mut app = App::new(storage, signal, my_number);
+    remove_view_once_copies();
+    let mut app = App::new(storage, Arc::new(signal), my_number);
+    app.load_settings();
+    if has_flag("--compact") {
+        app.list_density = ListDensity::Compact;
+    }
+    if has_flag("--read-receipts") || has_flag("--no-read-receipts") {
+        app.send_read_receipts = !has_flag("--no-read-receipts");
+        let value = app.send_read_receipts.to_string();
+        app.save_setting("send_read_receipts", &value);
+    }
+    app.link_preview_images = has_flag("--link-preview-images");
+    app.notifications_enabled = has_flag("--notify");
+    app.bell_enabled = has_flag("--bell");
+    if let Some(privacy) = parse_notification_privacy()? {
+        app.notification_privacy = privacy;
+        app.save_setting("notification_privacy", privacy.as_str());
+    }
+    if let Some(len) = parse_preview_length() {
+        app.preview_length = len;
+    }
+    app.max_message_width = parse_max_width();
+    app.timestamp_format = timestamp_format;
+    app.state_path = Some(state_path(&db_path));
+    app.voice_notes_dir = Some(get_data_dir().join("voice-notes"));
+    app.file_browser.load_bookmarks(get_data_dir().join("bookmarks"));
+    app.osc52_clipboard = has_flag("--osc52")
+        || std::env::var_os("SSH_TTY").is_some()
+        || std::env::var_os("SSH_CONNECTION").is_some();
+    app.audio_player = parse_audio_player();
     app.load_conversations();
-
-    if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
-        let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
+    if let Ok(contacts) = app.signal.list_contacts().await {
+        app.set_contacts(contacts);
     }
+    app.verify_before_send = !has_flag("--no-verify-before-send");
+    app.pending_identity_refresh = true;
 
     let mut avatar_manager = AvatarManager::new();
     let mut image_cache = ImageCache::new();
@@ -119,207 +728,588 @@ async fn main() -> anyhow::Result<()> {
     stdout().execute(cursor::Hide)?;
     stdout().execute(EnableFocusChange)?;
     stdout().execute(EnableMouseCapture)?;
+    stdout().execute(EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
     let mut needs_redraw = true;
+    let mut cell_size = image_cache::terminal_font_size();
+    let mut last_heartbeat = Instant::now();
+    let mut heartbeat_in_flight = false;
+    let mut reconnecting = false;
+    let directory_refresh = parse_directory_refresh();
+    let mut last_directory_refresh = Instant::now();
+    let mut last_spinner = Instant::now();
+    let mut last_draw = Instant::now();
+    let (task_tx, mut task_rx) = mpsc::unbounded_channel();
+    let update_title = has_flag("--terminal-title");
+    let mut title_unread = None;
+    let mut terminal_events = EventStream::new();
+    let mut incoming_open = true;
+    let mut syncing = false;
 
     loop {
-        if needs_redraw {
-            terminal
-                .draw(|frame| ui::render(frame, &mut app, &mut avatar_manager, &mut image_cache))?;
-            needs_redraw = false;
+        let pending_downloads = image_cache
+            .as_mut()
+            .map(|cache| cache.take_pending_downloads())
+            .unwrap_or_default();
+        for id in pending_downloads {
+            spawn_download(&app, &task_tx, id);
         }
 
-        if event::poll(Duration::from_millis(20))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    events::handle_key_event(&mut app, key);
+        for (title, body) in std::mem::take(&mut app.pending_notifications) {
+            send_desktop_notification(&title, &body);
+        }
 
-                    while event::poll(Duration::from_millis(0))? {
-                        if let Event::Key(next_key) = event::read()? {
-                            events::handle_key_event(&mut app, next_key);
-                        }
-                    }
-                    if let Some(ref mut cache) = image_cache {
-                        let paths = app.take_preload_paths();
-                        if !paths.is_empty() {
-                            // TODO: hardcoded max width
-                            cache.preload_images(&paths, 60);
-                        }
-                    }
+        if std::mem::take(&mut app.pending_bell) {
+            let mut out = stdout();
+            let _ = out.write_all(b"\x07");
+            let _ = out.flush();
+        }
 
-                    if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
-                        let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
-                    }
+        if !app.pending_typing.is_empty() {
+            let typing = std::mem::take(&mut app.pending_typing);
+            let signal = app.signal.clone();
+            tokio::spawn(async move {
+                for (target, started) in typing {
+                    let _ = match (&target, started) {
+                        (SendTarget::Direct(recipient), true) => signal.send_typing_started(recipient).await,
+                        (SendTarget::Direct(recipient), false) => signal.send_typing_stopped(recipient).await,
+                        (SendTarget::Group(group_id), true) => signal.send_group_typing_started(group_id).await,
+                        (SendTarget::Group(group_id), false) => signal.send_group_typing_stopped(group_id).await,
+                    };
+                }
+            });
+        }
 
-                    needs_redraw = true;
+        if let Some(identifier) = app.pending_trust.take() {
+            needs_redraw = true;
+            match app.signal.trust_identity(&identifier, true).await {
+                Ok(()) => app.pending_identity_refresh = true,
+                Err(e) => {
+                    app.status_message = Some(format!("Failed to trust safety number: {}", e));
+                    if let Some(text) = app.pending_send.take() {
+                        app.input.insert_str(&text);
+                    }
                 }
-                Event::Mouse(mouse_event) => {
-                    events::handle_mouse_event(&mut app, mouse_event);
+            }
+        }
+
+        if app.pending_identity_refresh {
+            app.pending_identity_refresh = false;
+            if let Ok(identities) = app.signal.list_identities().await {
+                app.set_identities(identities);
+                needs_redraw = true;
+            }
+        }
+
+        if let Some(text) = app.pending_send.take() {
+            let attachments = std::mem::take(&mut app.pending_attachments);
+            needs_redraw = true;
+            if let Some(target) = app.get_send_target() {
+                let conv_id = app
+                    .selected_conversation()
+                    .map(|c| c.conversation.id.clone());
+                let quote = app.take_reply();
+                let message = conv_id
+                    .map(|conv_id| add_outgoing_message(&mut app, &conv_id, &text, &attachments, quote));
+                let attachment_paths = attachment_path_strings(&attachments);
+                spawn_send(&mut app, &task_tx, &target, &text, attachment_paths, message);
+            }
+        }
+
+        if let Some(text) = app.pending_broadcast.take() {
+            let attachments = std::mem::take(&mut app.pending_attachments);
+            let conv_ids = std::mem::take(&mut app.broadcast_targets);
+            needs_redraw = true;
+            let mut skipped = Vec::new();
+            for conv_id in &conv_ids {
+                let Some(conv) = app.conversations.iter().find(|c| c.conversation.id == *conv_id)
+                else {
+                    continue;
+                };
+                let name = conv.conversation.display_name();
+                let Some(target) = conv.send_target() else {
+                    skipped.push(name);
+                    continue;
+                };
+                let message = add_outgoing_message(&mut app, conv_id, &text, &attachments, None);
+                let attachment_paths = attachment_path_strings(&attachments);
+                spawn_send(&mut app, &task_tx, &target, &text, attachment_paths, Some(message));
+            }
+            app.status_message = Some(if skipped.is_empty() {
+                format!("Broadcasting to {} conversations", conv_ids.len())
+            } else {
+                format!(
+                    "Broadcasting to {}/{} conversations, skipped: {}",
+                    conv_ids.len() - skipped.len(),
+                    conv_ids.len(),
+                    skipped.join(", ")
+                )
+            });
+        }
 
-                    while event::poll(Duration::from_millis(0))? {
-                        if let Event::Mouse(next_mouse) = event::read()? {
-                            events::handle_mouse_event(&mut app, next_mouse);
+        if let Some(message) = app.pending_retry.take() {
+            needs_redraw = true;
+            if let Some(target) = app.get_send_target() {
+                let (text, attachment_paths) = match &message.content {
+                    MessageContent::Text { body } => (body.clone(), Vec::new()),
+                    MessageContent::Attachment { attachments } => (
+                        String::new(),
+                        attachments.iter().filter_map(|a| a.local_path.clone()).collect(),
+                    ),
+                    _ => (String::new(), Vec::new()),
+                };
+                spawn_send(&mut app, &task_tx, &target, &text, attachment_paths, Some(message));
+            }
+        }
+
+        if let Some((conv_id, seconds)) = app.pending_expiration.take() {
+            needs_redraw = true;
+            let conv = app
+                .conversations
+                .iter()
+                .find(|c| c.conversation.id == conv_id)
+                .map(|c| c.conversation.clone());
+            if let Some(conv) = conv {
+                let result = match &conv.group_id {
+                    Some(group_id) => app.signal.set_group_expiration(group_id, seconds).await,
+                    None => {
+                        let recipient = conv.recipient_uuid.as_ref().or(conv.recipient_number.as_ref());
+                        match recipient {
+                            Some(recipient) => app.signal.set_expiration(recipient, seconds).await,
+                            None => Ok(()),
                         }
                     }
+                };
+                match result {
+                    Ok(()) => app.apply_expiration(&conv, seconds),
+                    Err(e) => app.status_message = Some(format!("Failed to set timer: {}", e)),
+                }
+            }
+        }
 
-                    if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
-                        let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
-                    }
+        if let Some(group_id) = app.pending_group_fetch.take() {
+            let storage = app.storage.clone();
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                let result = signal.get_group(&group_id).await;
+                let _ = tasks.send(TaskResult::GroupDetails { storage, result });
+            });
+        }
 
-                    needs_redraw = true;
+        if let Some(group_id) = app.pending_invite_link.take() {
+            needs_redraw = true;
+            match app.signal.get_group_invite_link(&group_id).await {
+                Ok(link) => app.show_invite_link(&group_id, link),
+                Err(SignalError::PermissionDenied(_)) => {
+                    app.status_message = Some("Only group admins can create invite links".to_string());
                 }
-                Event::Resize(_, _) => {
-                    needs_redraw = true;
+                Err(e) => app.status_message = Some(format!("Failed to create invite link: {}", e)),
+            }
+        }
+
+        if let Some(number) = app.pending_number_lookup.take() {
+            let storage = app.storage.clone();
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                let result = signal.get_user_status(std::slice::from_ref(&number)).await;
+                let _ = tasks.send(TaskResult::NumberLookup { storage, number, result });
+            });
+        }
+
+        let unchecked = app.take_unchecked_recipients();
+        if !unchecked.is_empty() {
+            let storage = app.storage.clone();
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                if let Ok(statuses) = signal.get_user_status(&unchecked).await {
+                    let _ = tasks.send(TaskResult::UserStatus { storage, statuses });
                 }
-                Event::FocusGained => {
-                    terminal.clear()?;
-                    needs_redraw = true;
+            });
+        }
+
+        for pending in std::mem::take(&mut app.pending_remote_deletes) {
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                for ts in pending.timestamps {
+                    let result = match &pending.target {
+                        RemoteDeleteTarget::Direct(recipient) => signal.remote_delete(recipient, ts).await,
+                        RemoteDeleteTarget::Group(group_id) => signal.remote_delete_group(group_id, ts).await,
+                    };
+                    if let Err(e) = result {
+                        let _ = tasks.send(TaskResult::RemoteDeleteFailed(e));
+                    }
                 }
-                _ => {}
+            });
+        }
+
+        if app.pending_account_list {
+            app.pending_account_list = false;
+            needs_redraw = true;
+            match app.signal.list_accounts().await {
+                Ok(accounts) => app.open_account_picker(accounts),
+                Err(e) => app.status_message = Some(format!("Failed to list accounts: {}", e)),
             }
         }
 
+        if let Some(account) = app.pending_account_switch.take() {
+            needs_redraw = true;
+            let account_db = database_path(Some(&account), passphrase.as_deref());
+            match SqliteStorage::open(&account_db, passphrase.as_deref()) {
+                Ok(storage) => match create_signal_client(Some(account.clone())) {
+                    Ok(signal) => match signal.connect().await {
+                        Ok(()) => {
+                            let _ = app.signal.disconnect().await;
+                            messages = signal.incoming_messages();
+                            incoming_open = true;
+                            app.switch_account(Arc::new(storage), state_path(&account_db), Arc::new(signal), account);
+                            if let Ok(contacts) = app.signal.list_contacts().await {
+                                app.set_contacts(contacts);
+                            }
+                            app.pending_identity_refresh = true;
+                            app.status_message = Some(format!("Switched to {}", app.my_number.as_deref().unwrap_or_default()));
+                        }
+                        Err(e) => app.status_message = Some(format!("Failed to switch account: {}", e)),
+                    },
+                    Err(e) => app.status_message = Some(format!("Failed to switch account: {}", e)),
+                },
+                Err(e) => app.status_message = Some(format!("Failed to open database: {}", e)),
+            }
+        }
+
+        if let Some(recording) = app.finishing_recording.take() {
+            let tasks = task_tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let _ = tasks.send(TaskResult::VoiceRecorded(recording.wait()));
+            });
+        }
+
+        if app.pending_unregister {
+            app.pending_unregister = false;
+            needs_redraw = true;
+            app.status_message = Some("Unregistering...".to_string());
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                let result = match signal.unregister(false).await {
+                    Ok(()) => signal.delete_local_account_data().await,
+                    Err(e) => Err(e),
+                };
+                let _ = tasks.send(TaskResult::Unregistered(result));
+            });
+        }
+
+        if app.should_quit {
+            break;
+        }
+
+        if app.has_pending_sends() && last_spinner.elapsed() >= SPINNER_INTERVAL {
+            last_spinner = Instant::now();
+            needs_redraw = true;
+        }
+
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL && !heartbeat_in_flight {
+            last_heartbeat = Instant::now();
+            heartbeat_in_flight = true;
+            let signal = app.signal.clone();
+            let tasks = task_tx.clone();
+            tokio::spawn(async move {
+                let ok = signal.ping().await.is_ok();
+                let _ = tasks.send(TaskResult::Heartbeat(ok));
+            });
+        }
+
+        if app.sync_stale_label().is_some() && last_draw.elapsed() >= HEARTBEAT_INTERVAL {
+            needs_redraw = true;
+        }
+
+        if let Some(interval) = directory_refresh
+            && last_directory_refresh.elapsed() >= interval
+        {
+            last_directory_refresh = Instant::now();
+            spawn_directory_refresh(&app, &task_tx);
+        }
+
+        if app.expire_flash() | app.expire_typing() | app.expire_snooze() {
+            needs_redraw = true;
+        }
+
         if let Some(ref mut cache) = image_cache
-            && cache.process_next_loaded_image()
+            && cache.retry_missing()
         {
             needs_redraw = true;
         }
 
-        // Check for incoming Signal messages
-        match tokio::time::timeout(Duration::from_millis(20), messages.recv()).await {
-            Ok(Ok(msg)) => {
-                app.handle_incoming_message(msg);
-                if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
-                    let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
+        let relative_time = matches!(app.timestamp_format, TimestampFormat::Relative);
+        if relative_time && last_draw.elapsed() >= RELATIVE_TIME_REFRESH {
+            needs_redraw = true;
+        }
+
+        if app.voice_recording.is_some() && last_draw.elapsed() >= RECORDING_REFRESH {
+            needs_redraw = true;
+        }
+
+        if needs_redraw {
+            terminal
+                .draw(|frame| ui::render(frame, &mut app, &mut avatar_manager, &mut image_cache))?;
+            needs_redraw = false;
+            last_draw = Instant::now();
+            if update_title {
+                let unread = app.total_unread();
+                if title_unread != Some(unread) {
+                    title_unread = Some(unread);
+                    stdout().execute(SetTitle(window_title(unread)))?;
                 }
-                needs_redraw = true;
-            }
-            Ok(Err(_)) => {
-                app.status_message = Some("Signal connection lost".to_string());
-                needs_redraw = true;
             }
-            Err(_) => {}
+            continue;
         }
 
-        if let Some(text) = app.pending_send.take() {
-            let attachments = std::mem::take(&mut app.pending_attachments);
-            needs_redraw = true;
-            if let Some(target) = app.get_send_target() {
-                let my_uuid = app.my_uuid.clone().unwrap_or_default();
-                let conv_id = app
-                    .selected_conversation()
-                    .map(|c| c.conversation.id.clone());
+        let mut deadline = last_heartbeat + HEARTBEAT_INTERVAL;
+        if app.has_pending_sends() {
+            deadline = deadline.min(last_spinner + SPINNER_INTERVAL);
+        }
+        if let Some(expiry) = app.next_flash_expiry() {
+            deadline = deadline.min(expiry);
+        }
+        if let Some(expiry) = app.next_typing_expiry() {
+            deadline = deadline.min(expiry);
+        }
+        if let Some(until) = app.snooze_until {
+            deadline = deadline.min(until);
+        }
+        if let Some(retry) = image_cache.as_ref().and_then(|cache| cache.next_retry()) {
+            deadline = deadline.min(retry);
+        }
+        if relative_time {
+            deadline = deadline.min(last_draw + RELATIVE_TIME_REFRESH);
+        }
+        if app.voice_recording.is_some() {
+            deadline = deadline.min(last_draw + RECORDING_REFRESH);
+        }
+        if let Some(interval) = directory_refresh {
+            deadline = deadline.min(last_directory_refresh + interval);
+        }
 
-                let content = if !attachments.is_empty() {
-                    let att_info: Vec<_> = attachments
-                        .iter()
-                        .map(|p| storage::AttachmentInfo {
-                            id: None,
-                            content_type: mime_from_path(p),
-                            filename: p.file_name().map(|n| n.to_string_lossy().to_string()),
-                            size: p.metadata().ok().map(|m| m.len()),
-                            local_path: Some(p.to_string_lossy().to_string()),
-                        })
-                        .collect();
-                    MessageContent::Attachment { attachments: att_info }
-                } else {
-                    MessageContent::Text { body: text.clone() }
-                };
+        let wake = tokio::select! {
+            event = terminal_events.next() => Wake::Terminal(event),
+            msg = messages.recv(), if incoming_open => Wake::Incoming(msg.map(Box::new)),
+            Some(result) = task_rx.recv() => Wake::Task(result),
+            _ = wait_for_loaded_image(&image_cache) => Wake::ImageLoaded,
+            _ = tokio::time::sleep_until(deadline.into()) => Wake::Timer,
+        };
 
-                let mut message = None;
-                if let Some(ref conv_id) = conv_id {
-                    let msg = Message {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        conversation_id: conv_id.clone(),
-                        sender_uuid: my_uuid,
-                        sender_name: None,
-                        timestamp: now_millis(),
-                        server_timestamp: None,
-                        received_at: now_millis(),
-                        content,
-                        quote: None,
-                        is_outgoing: true,
-                        is_read: true,
-                        is_deleted: false,
-                        is_edited: false,
+        match wake {
+            Wake::Terminal(None) => break,
+            Wake::Terminal(Some(event)) => {
+                let mut batch = vec![event?];
+                while let Some(Some(next)) = terminal_events.next().now_or_never() {
+                    batch.push(next?);
+                }
+                for event in batch {
+                    let interaction = match &event {
+                        Event::Key(_) | Event::Paste(_) => true,
+                        Event::Mouse(mouse_event) => mouse_event.kind != MouseEventKind::Moved,
+                        _ => false,
                     };
-                    app.add_message_to_conversation(conv_id, msg.clone());
-                    message = Some(msg);
+                    if interaction {
+                        app.read_marking_deferred = false;
+                    }
+                    match event {
+                        Event::Key(key) => events::handle_key_event(&mut app, key),
+                        Event::Mouse(mouse_event) => events::handle_mouse_event(&mut app, mouse_event),
+                        Event::Paste(text) => events::handle_paste(&mut app, &text),
+                        Event::Resize(_, _) => {
+                            let new_cell_size = image_cache::terminal_font_size();
+                            let font_size_changed = new_cell_size.is_some() && new_cell_size != cell_size;
+                            cell_size = new_cell_size;
+                            handle_resize(&mut image_cache, &mut avatar_manager, font_size_changed);
+                        }
+                        Event::FocusGained => {
+                            app.terminal_focused = true;
+                            terminal.clear()?;
+                        }
+                        Event::FocusLost => app.terminal_focused = false,
+                    }
                 }
 
-                for att_path in &attachments {
-                    if mime_from_path(att_path).is_some_and(|m| m.starts_with("image/")) {
-                        app.pending_preload_paths.push(att_path.to_string_lossy().to_string());
+                if let Some(ref mut cache) = image_cache {
+                    let paths = app.take_preload_paths();
+                    if !paths.is_empty() {
+                        // TODO: hardcoded max width
+                        cache.preload_images(&paths, 60);
                     }
                 }
 
-                let attachment_paths: Vec<String> = attachments
-                    .iter()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .collect();
-
-                let result = match &target {
-                    SendTarget::Direct(recipient) => {
-                        if attachment_paths.is_empty() {
-                            app.signal.send_message(recipient, &text).await
-                        } else {
-                            app.signal.send_message_with_attachments(recipient, &text, attachment_paths).await
-                        }
+                if app.refresh_avatars {
+                    app.refresh_avatars = false;
+                    if let Some(ref mut mgr) = avatar_manager {
+                        mgr.refresh();
                     }
-                    SendTarget::Group(group_id) => {
-                        app.signal.send_group_message(group_id, &text).await
+                    app.status_message = Some("Avatars refreshed".to_string());
+                }
+
+                if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
+                    let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
+                }
+
+                needs_redraw = true;
+            }
+            Wake::Incoming(Ok(msg)) => {
+                let mut batch = vec![*msg];
+                while batch.len() < SYNC_BATCH_SIZE
+                    && let Ok(next) = messages.try_recv()
+                {
+                    batch.push(next);
+                }
+                let batched = batch.len() > 1 && app.storage.begin_batch().is_ok();
+                for msg in batch {
+                    app.handle_incoming_message(msg);
+                }
+                if batched {
+                    let _ = app.storage.commit_batch();
+                }
+
+                let backlog = messages.len();
+                if backlog > 0 {
+                    syncing = true;
+                    app.status_message = Some(format!("Syncing {} messages…", backlog));
+                } else if syncing {
+                    syncing = false;
+                    if app.status_message.as_deref().is_some_and(|s| s.starts_with("Syncing")) {
+                        app.status_message = None;
                     }
-                };
+                }
 
+                if let Some((recipient, timestamps)) = app.mark_current_conversation_read() {
+                    let _ = app.signal.send_read_receipt(&recipient, timestamps).await;
+                }
+                needs_redraw = true;
+            }
+            Wake::Incoming(Err(broadcast::error::RecvError::Lagged(missed))) => {
+                app.status_message = Some(format!("Fell behind while syncing, skipped {} messages", missed));
+                needs_redraw = true;
+            }
+            Wake::Incoming(Err(broadcast::error::RecvError::Closed)) => {
+                incoming_open = false;
+                app.status_message = Some("Signal connection lost".to_string());
+                needs_redraw = true;
+            }
+            Wake::Task(result) => {
+                needs_redraw = true;
                 match result {
-                    Ok(send_result) => {
-                        if let Some(mut msg) = message {
-                            let old_ts = msg.timestamp;
-                            if let Some(ts) = send_result.timestamp {
-                                msg.timestamp = ts;
-                                if let Some(conv) = app.selected_conversation_mut() {
-                                    if let Some(ref mut msgs) = conv.messages {
-                                        if let Some(m) = msgs.iter_mut().find(|m| m.timestamp == old_ts) {
-                                            m.timestamp = ts;
-                                        }
-                                    }
-                                }
+                    TaskResult::Sent { storage, message, recipient, result } => {
+                        apply_send_result(&mut app, &storage, message.map(|m| *m), &recipient, result);
+                    }
+                    TaskResult::RemoteDeleteFailed(e) => {
+                        app.status_message = Some(format!("Remote delete failed: {}", e));
+                    }
+                    TaskResult::Heartbeat(ok) => {
+                        heartbeat_in_flight = false;
+                        let health = app.record_heartbeat(ok);
+                        if health == ConnectionHealth::Unhealthy && !reconnecting {
+                            reconnecting = true;
+                            app.status_message = Some("signal-cli not responding, reconnecting...".to_string());
+                            let signal = app.signal.clone();
+                            let tasks = task_tx.clone();
+                            tokio::spawn(async move {
+                                let _ = tasks.send(TaskResult::Reconnected(signal.reconnect().await));
+                            });
+                        }
+                    }
+                    TaskResult::Reconnected(result) => {
+                        reconnecting = false;
+                        match result {
+                            Ok(()) => {
+                                app.record_heartbeat(true);
+                                app.status_message = Some("Reconnected to signal-cli".to_string());
                             }
-                            let _ = app.storage.save_message(&msg);
+                            Err(e) => app.status_message = Some(format!("Reconnect failed: {}", e)),
                         }
                     }
-                    Err(e) => {
-                        app.status_message = Some(format!("Send failed: {}", e));
+                    TaskResult::Downloaded { id, success } => {
+                        if let Some(ref mut cache) = image_cache {
+                            cache.download_finished(&id, success);
+                        }
                     }
-                }
-            }
-        }
-
-        for pending in std::mem::take(&mut app.pending_remote_deletes) {
-            for ts in pending.timestamps {
-                let result = match &pending.target {
-                    RemoteDeleteTarget::Direct(recipient) => {
-                        app.signal.remote_delete(recipient, ts).await
+                    TaskResult::Directory { storage, contacts, groups } => {
+                        if Arc::ptr_eq(&storage, &app.storage) {
+                            app.apply_directory(contacts, groups);
+                        }
                     }
-                    RemoteDeleteTarget::Group(group_id) => {
-                        app.signal.remote_delete_group(group_id, ts).await
+                    TaskResult::GroupDetails { storage, result } => {
+                        if Arc::ptr_eq(&storage, &app.storage) {
+                            match result {
+                                Ok(group) => app.apply_group_details(&group),
+                                Err(e) => app.status_message = Some(format!("Failed to load group: {}", e)),
+                            }
+                        }
+                    }
+                    TaskResult::UserStatus { storage, statuses } => {
+                        if Arc::ptr_eq(&storage, &app.storage) {
+                            app.record_user_status(statuses);
+                        }
+                    }
+                    TaskResult::NumberLookup { storage, number, result } => {
+                        if Arc::ptr_eq(&storage, &app.storage) {
+                            match result {
+                                Ok(statuses) => app.apply_number_lookup(&number, statuses),
+                                Err(e) => app.status_message = Some(format!("Failed to look up {}: {}", number, e)),
+                            }
+                        }
+                    }
+                    TaskResult::VoiceRecorded(path) => app.attach_voice_recording(path),
+                    TaskResult::Unregistered(Ok(())) => {
+                        app.status_message = match app.storage.clear_all() {
+                            Ok(()) => None,
+                            Err(e) => Some(format!("Failed to clear local data: {}", e)),
+                        };
+                        if let Some(path) = app.state_path.take() {
+                            let _ = std::fs::remove_file(path);
+                        }
+                        app.load_conversations();
+                        match create_signal_client(None) {
+                            Ok(signal) => spawn_link(signal, &task_tx),
+                            Err(e) => app.status_message = Some(format!("Failed to start linking: {}", e)),
+                        }
+                    }
+                    TaskResult::Unregistered(Err(e)) => {
+                        app.status_message = Some(format!("Unregister failed: {}", e));
+                    }
+                    TaskResult::LinkStarted(Ok(uri)) => app.device_link = Some(uri),
+                    TaskResult::LinkStarted(Err(e)) => {
+                        app.status_message = Some(format!("Failed to start linking: {}", e));
+                    }
+                    TaskResult::Linked(result) => {
+                        app.device_link = None;
+                        match result {
+                            Ok(number) => app.pending_account_switch = Some(number),
+                            Err(e) => app.status_message = Some(format!("Linking failed: {}", e)),
+                        }
                     }
-                };
-                if let Err(e) = result {
-                    app.status_message = Some(format!("Remote delete failed: {}", e));
                 }
             }
-        }
-
-        if app.should_quit {
-            break;
+            Wake::ImageLoaded => {
+                while let Some(ref mut cache) = image_cache
+                    && cache.process_next_loaded_image()
+                {
+                    needs_redraw = true;
+                }
+            }
+            Wake::Timer => {}
         }
     }
 
+    app.save_state();
+    remove_view_once_copies();
+
     stdout().execute(cursor::Show)?;
+    stdout().execute(DisableBracketedPaste)?;
     stdout().execute(DisableMouseCapture)?;
     stdout().execute(DisableFocusChange)?;
     terminal::disable_raw_mode()?;
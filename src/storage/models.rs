@@ -19,6 +19,10 @@ pub struct Conversation {
     pub unread_count: u32,
     pub is_archived: bool,
     pub is_muted: bool,
+    pub local_alias: Option<String>,
+    pub expiration_seconds: u32,
+    pub color: Option<String>,
+    pub marked_unread_timestamp: Option<i64>,
 }
 
 impl Conversation {
@@ -39,6 +43,10 @@ impl Conversation {
             unread_count: 0,
             is_archived: false,
             is_muted: false,
+            local_alias: None,
+            expiration_seconds: 0,
+            color: None,
+            marked_unread_timestamp: None,
         }
     }
 
@@ -55,11 +63,18 @@ impl Conversation {
             unread_count: 0,
             is_archived: false,
             is_muted: false,
+            local_alias: None,
+            expiration_seconds: 0,
+            color: None,
+            marked_unread_timestamp: None,
         }
     }
 
     pub fn display_name(&self) -> String {
         let non_empty = |s: &Option<String>| s.as_ref().filter(|s| !s.is_empty()).cloned();
+        if let Some(alias) = non_empty(&self.local_alias) {
+            return alias;
+        }
         match self.conversation_type {
             ConversationType::Direct => non_empty(&self.recipient_name)
                 .or_else(|| non_empty(&self.recipient_number))
@@ -86,6 +101,10 @@ pub struct AttachmentInfo {
     pub filename: Option<String>,
     pub size: Option<u64>,
     pub local_path: Option<String>,
+    #[serde(default)]
+    pub view_once: bool,
+    #[serde(default)]
+    pub viewed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +112,32 @@ pub struct Quote {
     pub author_uuid: String,
     pub timestamp: i64,
     pub text: Option<String>,
+    #[serde(default)]
+    pub attachment_name: Option<String>,
+}
+
+impl Quote {
+    pub fn summary(&self) -> Option<String> {
+        self.text
+            .clone()
+            .filter(|t| !t.is_empty())
+            .or_else(|| self.attachment_name.as_ref().map(|name| format!("📎 {}", name)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_path: Option<String>,
+}
+
+impl LinkPreview {
+    pub fn host(&self) -> &str {
+        let rest = self.url.split_once("://").map_or(self.url.as_str(), |(_, rest)| rest);
+        rest.split(['/', '?', '#']).next().unwrap_or(rest)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,12 +155,34 @@ pub struct Message {
     pub is_read: bool,
     pub is_deleted: bool,
     pub is_edited: bool,
+    pub link_preview: Option<LinkPreview>,
 }
 
 impl Message {
     pub fn signal_id(&self) -> (String, i64) {
         (self.sender_uuid.clone(), self.timestamp)
     }
+
+    pub fn sort_key(&self) -> (i64, Option<i64>, i64, &str) {
+        (self.timestamp, self.server_timestamp, self.received_at, &self.id)
+    }
+
+    pub fn cursor(&self) -> MessageCursor {
+        MessageCursor {
+            timestamp: self.timestamp,
+            server_timestamp: self.server_timestamp,
+            received_at: self.received_at,
+            id: self.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageCursor {
+    pub timestamp: i64,
+    pub server_timestamp: Option<i64>,
+    pub received_at: i64,
+    pub id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
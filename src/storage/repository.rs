@@ -1,4 +1,5 @@
 use super::models::*;
+use std::collections::HashMap;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,6 +12,9 @@ pub enum StorageError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
 }
 
 pub trait StorageRepository: Send + Sync {
@@ -45,6 +49,10 @@ pub trait StorageRepository: Send + Sync {
 
     fn save_message(&self, message: &Message) -> Result<(), StorageError>;
 
+    fn begin_batch(&self) -> Result<(), StorageError>;
+
+    fn commit_batch(&self) -> Result<(), StorageError>;
+
     fn get_message(&self, id: &str) -> Result<Option<Message>, StorageError>;
 
     fn get_message_by_signal_id(
@@ -53,11 +61,17 @@ pub trait StorageRepository: Send + Sync {
         timestamp: i64,
     ) -> Result<Option<Message>, StorageError>;
 
+    fn first_message_on_or_after(
+        &self,
+        conversation_id: &str,
+        timestamp: i64,
+    ) -> Result<Option<Message>, StorageError>;
+
     fn list_messages(
         &self,
         conversation_id: &str,
         limit: u32,
-        before_timestamp: Option<i64>,
+        before: Option<&MessageCursor>,
     ) -> Result<Vec<Message>, StorageError>;
 
     fn delete_message(&self, id: &str) -> Result<(), StorageError>;
@@ -82,13 +96,35 @@ pub trait StorageRepository: Send + Sync {
 
     fn get_reactions(&self, message_id: &str) -> Result<Vec<Reaction>, StorageError>;
 
+    fn get_reactions_for_messages(
+        &self,
+        message_ids: &[String],
+    ) -> Result<HashMap<String, Vec<Reaction>>, StorageError>;
+
     fn save_delivery_status(&self, status: &DeliveryStatus) -> Result<(), StorageError>;
 
-    fn get_delivery_statuses(&self, message_id: &str) -> Result<Vec<DeliveryStatus>, StorageError>;
+    fn get_delivery_statuses_for_messages(
+        &self,
+        message_ids: &[String],
+    ) -> Result<HashMap<String, Vec<DeliveryStatus>>, StorageError>;
+
+    fn save_group_members(&self, group_id: &str, members: &[GroupMember]) -> Result<(), StorageError>;
+
+    fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMember>, StorageError>;
 
     fn mark_messages_read(
         &self,
         conversation_id: &str,
         up_to_timestamp: i64,
     ) -> Result<(), StorageError>;
+
+    fn mark_conversation_unread(&self, conversation_id: &str) -> Result<(), StorageError>;
+
+    fn get_setting(&self, key: &str) -> Result<Option<String>, StorageError>;
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), StorageError>;
+
+    fn clear_all(&self) -> Result<(), StorageError>;
+
+    fn vacuum(&self) -> Result<(), StorageError>;
 }
@@ -82,6 +82,39 @@ const MIGRATIONS: &[&str] = &[
     ALTER TABLE messages ADD COLUMN is_edited INTEGER NOT NULL DEFAULT 0;
     UPDATE schema_version SET version = 2;
     "#,
+    // Migration 3: Add local_alias column to conversations
+    r#"
+    ALTER TABLE conversations ADD COLUMN local_alias TEXT;
+    UPDATE schema_version SET version = 3;
+    "#,
+    // Migration 4: Add expiration_seconds column to conversations
+    r#"
+    ALTER TABLE conversations ADD COLUMN expiration_seconds INTEGER NOT NULL DEFAULT 0;
+    UPDATE schema_version SET version = 4;
+    "#,
+    // Migration 5: Add preview_json column to messages
+    r#"
+    ALTER TABLE messages ADD COLUMN preview_json TEXT;
+    UPDATE schema_version SET version = 5;
+    "#,
+    // Migration 6: Add settings table for persisted UI preferences
+    r#"
+    CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    UPDATE schema_version SET version = 6;
+    "#,
+    // Migration 7: Add per-conversation accent color
+    r#"
+    ALTER TABLE conversations ADD COLUMN color TEXT;
+    UPDATE schema_version SET version = 7;
+    "#,
+    // Migration 8: Remember messages the user marked unread
+    r#"
+    ALTER TABLE conversations ADD COLUMN marked_unread_timestamp INTEGER;
+    UPDATE schema_version SET version = 8;
+    "#,
 ];
 
 pub fn run_migrations(conn: &Connection) -> Result<(), StorageError> {
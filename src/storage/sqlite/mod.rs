@@ -2,18 +2,26 @@ mod migrations;
 
 use super::models::*;
 use super::repository::{StorageError, StorageRepository};
-use rusqlite::{Connection, OptionalExtension, params};
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Mutex;
 
+const MAX_IN_PARAMS: usize = 500;
+
 pub struct SqliteStorage {
     conn: Mutex<Connection>,
 }
 
 impl SqliteStorage {
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+    pub fn open<P: AsRef<Path>>(path: P, passphrase: Option<&str>) -> Result<Self, StorageError> {
         let conn = Connection::open(path).map_err(|e| StorageError::Database(e.to_string()))?;
 
+        if let Some(passphrase) = passphrase {
+            Self::apply_key(&conn, passphrase)?;
+        }
+        Self::verify_readable(&conn, passphrase.is_some())?;
+
         let storage = Self {
             conn: Mutex::new(conn),
         };
@@ -21,11 +29,75 @@ impl SqliteStorage {
         Ok(storage)
     }
 
+    #[cfg(feature = "sqlcipher")]
+    fn apply_key(conn: &Connection, passphrase: &str) -> Result<(), StorageError> {
+        conn.pragma_update(None, "key", passphrase)
+            .map_err(|e| StorageError::Encryption(e.to_string()))
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    fn apply_key(_conn: &Connection, _passphrase: &str) -> Result<(), StorageError> {
+        Err(StorageError::Encryption(
+            "a passphrase was given but signal-tty was built without the sqlcipher feature"
+                .to_string(),
+        ))
+    }
+
+    fn verify_readable(conn: &Connection, has_passphrase: bool) -> Result<(), StorageError> {
+        match conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)) {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::NotADatabase =>
+            {
+                Err(StorageError::Encryption(if has_passphrase {
+                    "wrong passphrase, or the database is not encrypted".to_string()
+                } else {
+                    "the database is encrypted, a passphrase is required".to_string()
+                }))
+            }
+            Err(e) => Err(StorageError::Database(e.to_string())),
+        }
+    }
+
     fn run_migrations(&self) -> Result<(), StorageError> {
         let conn = self.conn.lock().unwrap();
         migrations::run_migrations(&conn)
     }
 
+    fn get_direct_conversation_by_number(
+        &self,
+        recipient_number: &str,
+    ) -> Result<Option<Conversation>, StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, conversation_type, recipient_uuid, recipient_number, recipient_name,
+                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted,
+                    local_alias, expiration_seconds, color, marked_unread_timestamp
+             FROM conversations WHERE recipient_number = ?1 AND conversation_type = 'direct'
+             ORDER BY recipient_uuid = recipient_number LIMIT 1",
+            params![recipient_number],
+            |row| {
+                Ok(Conversation {
+                    id: row.get(0)?,
+                    conversation_type: Self::parse_conversation_type(&row.get::<_, String>(1)?),
+                    recipient_uuid: row.get(2)?,
+                    recipient_number: row.get(3)?,
+                    recipient_name: row.get(4)?,
+                    group_id: row.get(5)?,
+                    group_name: row.get(6)?,
+                    last_message_timestamp: row.get(7)?,
+                    unread_count: row.get(8)?,
+                    is_archived: row.get::<_, i32>(9)? != 0,
+                    is_muted: row.get::<_, i32>(10)? != 0,
+                    local_alias: row.get(11)?,
+                    expiration_seconds: row.get(12)?,
+                    color: row.get(13)?,
+                    marked_unread_timestamp: row.get(14)?,
+                })
+            },
+        ).optional().map_err(|e| StorageError::Database(e.to_string()))
+    }
+
     fn parse_conversation_type(s: &str) -> ConversationType {
         match s {
             "group" => ConversationType::Group,
@@ -40,6 +112,10 @@ impl SqliteStorage {
         }
     }
 
+    fn placeholders(count: usize) -> String {
+        vec!["?"; count].join(", ")
+    }
+
     fn parse_message_content(content_type: &str, content_data: &str) -> MessageContent {
         match content_type {
             "text" => MessageContent::Text {
@@ -100,8 +176,19 @@ impl StorageRepository for SqliteStorage {
         recipient_number: Option<&str>,
         recipient_name: Option<&str>,
     ) -> Result<Conversation, StorageError> {
-        if let Some(conv) = self.get_conversation_by_recipient(recipient_uuid)? {
-            if recipient_name.is_some() || recipient_number.is_some() {
+        let existing = match self.get_conversation_by_recipient(recipient_uuid)? {
+            Some(conv) => Some(conv),
+            None => match recipient_number {
+                Some(number) => self.get_direct_conversation_by_number(number)?,
+                None => None,
+            },
+        };
+
+        if let Some(conv) = existing {
+            let is_placeholder = conv.recipient_uuid.is_some()
+                && conv.recipient_uuid == conv.recipient_number
+                && Some(recipient_uuid) != recipient_number;
+            if recipient_name.is_some() || recipient_number.is_some() || is_placeholder {
                 let mut updated = conv.clone();
                 if recipient_name.is_some() {
                     updated.recipient_name = recipient_name.map(|s| s.to_string());
@@ -109,6 +196,9 @@ impl StorageRepository for SqliteStorage {
                 if recipient_number.is_some() {
                     updated.recipient_number = recipient_number.map(|s| s.to_string());
                 }
+                if is_placeholder {
+                    updated.recipient_uuid = Some(recipient_uuid.to_string());
+                }
                 self.update_conversation(&updated)?;
                 return Ok(updated);
             }
@@ -173,7 +263,8 @@ impl StorageRepository for SqliteStorage {
         let conn = self.conn.lock().unwrap();
         conn.query_row(
             "SELECT id, conversation_type, recipient_uuid, recipient_number, recipient_name,
-                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted
+                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted,
+                    local_alias, expiration_seconds, color, marked_unread_timestamp
              FROM conversations WHERE id = ?1",
             params![id],
             |row| {
@@ -189,6 +280,10 @@ impl StorageRepository for SqliteStorage {
                     unread_count: row.get(8)?,
                     is_archived: row.get::<_, i32>(9)? != 0,
                     is_muted: row.get::<_, i32>(10)? != 0,
+                    local_alias: row.get(11)?,
+                    expiration_seconds: row.get(12)?,
+                    color: row.get(13)?,
+                    marked_unread_timestamp: row.get(14)?,
                 })
             },
         ).optional().map_err(|e| StorageError::Database(e.to_string()))
@@ -199,10 +294,13 @@ impl StorageRepository for SqliteStorage {
         recipient_uuid: &str,
     ) -> Result<Option<Conversation>, StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.query_row(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, conversation_type, recipient_uuid, recipient_number, recipient_name,
-                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted
-             FROM conversations WHERE recipient_uuid = ?1 AND conversation_type = 'direct'",
+                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted,
+                    local_alias, expiration_seconds, color, marked_unread_timestamp
+             FROM conversations WHERE recipient_uuid = ?1 AND conversation_type = 'direct'"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.query_row(
             params![recipient_uuid],
             |row| {
                 Ok(Conversation {
@@ -217,6 +315,10 @@ impl StorageRepository for SqliteStorage {
                     unread_count: row.get(8)?,
                     is_archived: row.get::<_, i32>(9)? != 0,
                     is_muted: row.get::<_, i32>(10)? != 0,
+                    local_alias: row.get(11)?,
+                    expiration_seconds: row.get(12)?,
+                    color: row.get(13)?,
+                    marked_unread_timestamp: row.get(14)?,
                 })
             },
         ).optional().map_err(|e| StorageError::Database(e.to_string()))
@@ -227,10 +329,13 @@ impl StorageRepository for SqliteStorage {
         group_id: &str,
     ) -> Result<Option<Conversation>, StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.query_row(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, conversation_type, recipient_uuid, recipient_number, recipient_name,
-                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted
-             FROM conversations WHERE group_id = ?1 AND conversation_type = 'group'",
+                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted,
+                    local_alias, expiration_seconds, color, marked_unread_timestamp
+             FROM conversations WHERE group_id = ?1 AND conversation_type = 'group'"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.query_row(
             params![group_id],
             |row| {
                 Ok(Conversation {
@@ -245,6 +350,10 @@ impl StorageRepository for SqliteStorage {
                     unread_count: row.get(8)?,
                     is_archived: row.get::<_, i32>(9)? != 0,
                     is_muted: row.get::<_, i32>(10)? != 0,
+                    local_alias: row.get(11)?,
+                    expiration_seconds: row.get(12)?,
+                    color: row.get(13)?,
+                    marked_unread_timestamp: row.get(14)?,
                 })
             },
         ).optional().map_err(|e| StorageError::Database(e.to_string()))
@@ -254,7 +363,8 @@ impl StorageRepository for SqliteStorage {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
             "SELECT id, conversation_type, recipient_uuid, recipient_number, recipient_name,
-                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted
+                    group_id, group_name, last_message_timestamp, unread_count, is_archived, is_muted,
+                    local_alias, expiration_seconds, color, marked_unread_timestamp
              FROM conversations
              ORDER BY last_message_timestamp DESC NULLS LAST"
         ).map_err(|e| StorageError::Database(e.to_string()))?;
@@ -273,6 +383,10 @@ impl StorageRepository for SqliteStorage {
                     unread_count: row.get(8)?,
                     is_archived: row.get::<_, i32>(9)? != 0,
                     is_muted: row.get::<_, i32>(10)? != 0,
+                    local_alias: row.get(11)?,
+                    expiration_seconds: row.get(12)?,
+                    color: row.get(13)?,
+                    marked_unread_timestamp: row.get(14)?,
                 })
             })
             .map_err(|e| StorageError::Database(e.to_string()))?;
@@ -283,7 +397,7 @@ impl StorageRepository for SqliteStorage {
 
     fn update_conversation(&self, conversation: &Conversation) -> Result<(), StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "UPDATE conversations SET
                 recipient_number = ?2,
                 recipient_name = ?3,
@@ -291,8 +405,15 @@ impl StorageRepository for SqliteStorage {
                 last_message_timestamp = ?5,
                 unread_count = ?6,
                 is_archived = ?7,
-                is_muted = ?8
-             WHERE id = ?1",
+                is_muted = ?8,
+                local_alias = ?9,
+                expiration_seconds = ?10,
+                recipient_uuid = ?11,
+                color = ?12,
+                marked_unread_timestamp = ?13
+             WHERE id = ?1"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![
                 conversation.id,
                 conversation.recipient_number,
@@ -302,25 +423,48 @@ impl StorageRepository for SqliteStorage {
                 conversation.unread_count,
                 conversation.is_archived as i32,
                 conversation.is_muted as i32,
+                conversation.local_alias,
+                conversation.expiration_seconds,
+                conversation.recipient_uuid,
+                conversation.color,
+                conversation.marked_unread_timestamp,
             ],
         )
         .map_err(|e| StorageError::Database(e.to_string()))?;
         Ok(())
     }
 
+    fn begin_batch(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("BEGIN")
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn commit_batch(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("COMMIT")
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
     fn save_message(&self, message: &Message) -> Result<(), StorageError> {
         let (content_type, content_data) = Self::message_content_to_parts(&message.content);
         let quote_json = message
             .quote
             .as_ref()
             .map(|q| serde_json::to_string(q).unwrap_or_default());
+        let preview_json = message
+            .link_preview
+            .as_ref()
+            .map(|p| serde_json::to_string(p).unwrap_or_default());
 
         let conn = self.conn.lock().unwrap();
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "INSERT OR REPLACE INTO messages
              (id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
-              content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+              content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![
                 message.id,
                 message.conversation_id,
@@ -336,12 +480,15 @@ impl StorageRepository for SqliteStorage {
                 message.is_read as i32,
                 message.is_deleted as i32,
                 message.is_edited as i32,
+                preview_json,
             ],
         ).map_err(|e| StorageError::Database(e.to_string()))?;
 
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "UPDATE conversations SET last_message_timestamp = MAX(COALESCE(last_message_timestamp, 0), ?2)
-             WHERE id = ?1",
+             WHERE id = ?1"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![message.conversation_id, message.timestamp],
         ).map_err(|e| StorageError::Database(e.to_string()))?;
 
@@ -350,15 +497,18 @@ impl StorageRepository for SqliteStorage {
 
     fn get_message(&self, id: &str) -> Result<Option<Message>, StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.query_row(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
-                    content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited
-             FROM messages WHERE id = ?1",
+                    content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json
+             FROM messages WHERE id = ?1"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.query_row(
             params![id],
             |row| {
                 let content_type: String = row.get(7)?;
                 let content_data: String = row.get(8)?;
                 let quote_json: Option<String> = row.get(9)?;
+                let preview_json: Option<String> = row.get(14)?;
 
                 Ok(Message {
                     id: row.get(0)?,
@@ -374,6 +524,7 @@ impl StorageRepository for SqliteStorage {
                     is_read: row.get::<_, i32>(11)? != 0,
                     is_deleted: row.get::<_, i32>(12)? != 0,
                     is_edited: row.get::<_, i32>(13)? != 0,
+                    link_preview: preview_json.and_then(|s| serde_json::from_str(&s).ok()),
                 })
             },
         ).optional().map_err(|e| StorageError::Database(e.to_string()))
@@ -385,15 +536,56 @@ impl StorageRepository for SqliteStorage {
         timestamp: i64,
     ) -> Result<Option<Message>, StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.query_row(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
-                    content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited
-             FROM messages WHERE sender_uuid = ?1 AND timestamp = ?2",
+                    content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json
+             FROM messages WHERE sender_uuid = ?1 AND timestamp = ?2"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.query_row(
             params![sender_uuid, timestamp],
             |row| {
                 let content_type: String = row.get(7)?;
                 let content_data: String = row.get(8)?;
                 let quote_json: Option<String> = row.get(9)?;
+                let preview_json: Option<String> = row.get(14)?;
+
+                Ok(Message {
+                    id: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    sender_uuid: row.get(2)?,
+                    sender_name: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    server_timestamp: row.get(5)?,
+                    received_at: row.get(6)?,
+                    content: Self::parse_message_content(&content_type, &content_data),
+                    quote: quote_json.and_then(|s| serde_json::from_str(&s).ok()),
+                    is_outgoing: row.get::<_, i32>(10)? != 0,
+                    is_read: row.get::<_, i32>(11)? != 0,
+                    is_deleted: row.get::<_, i32>(12)? != 0,
+                    is_edited: row.get::<_, i32>(13)? != 0,
+                    link_preview: preview_json.and_then(|s| serde_json::from_str(&s).ok()),
+                })
+            },
+        ).optional().map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn first_message_on_or_after(
+        &self,
+        conversation_id: &str,
+        timestamp: i64,
+    ) -> Result<Option<Message>, StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
+                    content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json
+             FROM messages WHERE conversation_id = ?1 AND timestamp >= ?2
+             ORDER BY timestamp ASC, server_timestamp ASC, received_at ASC, id ASC LIMIT 1",
+            params![conversation_id, timestamp],
+            |row| {
+                let content_type: String = row.get(7)?;
+                let content_data: String = row.get(8)?;
+                let quote_json: Option<String> = row.get(9)?;
+                let preview_json: Option<String> = row.get(14)?;
 
                 Ok(Message {
                     id: row.get(0)?,
@@ -409,6 +601,7 @@ impl StorageRepository for SqliteStorage {
                     is_read: row.get::<_, i32>(11)? != 0,
                     is_deleted: row.get::<_, i32>(12)? != 0,
                     is_edited: row.get::<_, i32>(13)? != 0,
+                    link_preview: preview_json.and_then(|s| serde_json::from_str(&s).ok()),
                 })
             },
         ).optional().map_err(|e| StorageError::Database(e.to_string()))
@@ -418,32 +611,38 @@ impl StorageRepository for SqliteStorage {
         &self,
         conversation_id: &str,
         limit: u32,
-        before_timestamp: Option<i64>,
+        before: Option<&MessageCursor>,
     ) -> Result<Vec<Message>, StorageError> {
         let conn = self.conn.lock().unwrap();
 
-        let (sql, params_vec): (&str, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(ts) =
-            before_timestamp
-        {
+        let (sql, params_vec): (&str, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(cursor) = before {
             (
                 "SELECT id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
-                        content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited
-                 FROM messages WHERE conversation_id = ?1 AND timestamp < ?2
-                 ORDER BY timestamp DESC LIMIT ?3",
-                vec![Box::new(conversation_id.to_string()), Box::new(ts), Box::new(limit)]
+                        content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json
+                 FROM messages WHERE conversation_id = ?1
+                   AND (timestamp, IFNULL(server_timestamp, -1), received_at, id) < (?2, IFNULL(?3, -1), ?4, ?5)
+                 ORDER BY timestamp DESC, server_timestamp DESC, received_at DESC, id DESC LIMIT ?6",
+                vec![
+                    Box::new(conversation_id.to_string()),
+                    Box::new(cursor.timestamp),
+                    Box::new(cursor.server_timestamp),
+                    Box::new(cursor.received_at),
+                    Box::new(cursor.id.clone()),
+                    Box::new(limit),
+                ]
             )
         } else {
             (
                 "SELECT id, conversation_id, sender_uuid, sender_name, timestamp, server_timestamp, received_at,
-                        content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited
+                        content_type, content_data, quote_json, is_outgoing, is_read, is_deleted, is_edited, preview_json
                  FROM messages WHERE conversation_id = ?1
-                 ORDER BY timestamp DESC LIMIT ?2",
+                 ORDER BY timestamp DESC, server_timestamp DESC, received_at DESC, id DESC LIMIT ?2",
                 vec![Box::new(conversation_id.to_string()), Box::new(limit)]
             )
         };
 
         let mut stmt = conn
-            .prepare(sql)
+            .prepare_cached(sql)
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
         let params_refs: Vec<&dyn rusqlite::ToSql> =
@@ -454,6 +653,7 @@ impl StorageRepository for SqliteStorage {
                 let content_type: String = row.get(7)?;
                 let content_data: String = row.get(8)?;
                 let quote_json: Option<String> = row.get(9)?;
+                let preview_json: Option<String> = row.get(14)?;
 
                 Ok(Message {
                     id: row.get(0)?,
@@ -469,6 +669,7 @@ impl StorageRepository for SqliteStorage {
                     is_read: row.get::<_, i32>(11)? != 0,
                     is_deleted: row.get::<_, i32>(12)? != 0,
                     is_edited: row.get::<_, i32>(13)? != 0,
+                    link_preview: preview_json.and_then(|s| serde_json::from_str(&s).ok()),
                 })
             })
             .map_err(|e| StorageError::Database(e.to_string()))?;
@@ -518,9 +719,11 @@ impl StorageRepository for SqliteStorage {
 
     fn save_reaction(&self, reaction: &Reaction) -> Result<(), StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "INSERT OR REPLACE INTO reactions (id, message_id, sender_uuid, emoji, timestamp)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+             VALUES (?1, ?2, ?3, ?4, ?5)"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![
                 reaction.id,
                 reaction.message_id,
@@ -570,6 +773,40 @@ impl StorageRepository for SqliteStorage {
             .map_err(|e| StorageError::Database(e.to_string()))
     }
 
+    fn get_reactions_for_messages(
+        &self,
+        message_ids: &[String],
+    ) -> Result<HashMap<String, Vec<Reaction>>, StorageError> {
+        let mut grouped: HashMap<String, Vec<Reaction>> = HashMap::new();
+        let conn = self.conn.lock().unwrap();
+        for chunk in message_ids.chunks(MAX_IN_PARAMS) {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT id, message_id, sender_uuid, emoji, timestamp FROM reactions WHERE message_id IN ({})",
+                    Self::placeholders(chunk.len())
+                ))
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+
+            let rows = stmt
+                .query_map(params_from_iter(chunk), |row| {
+                    Ok(Reaction {
+                        id: row.get(0)?,
+                        message_id: row.get(1)?,
+                        sender_uuid: row.get(2)?,
+                        emoji: row.get(3)?,
+                        timestamp: row.get(4)?,
+                    })
+                })
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+
+            for row in rows {
+                let reaction = row.map_err(|e| StorageError::Database(e.to_string()))?;
+                grouped.entry(reaction.message_id.clone()).or_default().push(reaction);
+            }
+        }
+        Ok(grouped)
+    }
+
     fn save_delivery_status(&self, status: &DeliveryStatus) -> Result<(), StorageError> {
         let state_str = match status.state {
             DeliveryState::Sending => "sending",
@@ -580,9 +817,11 @@ impl StorageRepository for SqliteStorage {
         };
 
         let conn = self.conn.lock().unwrap();
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "INSERT OR REPLACE INTO delivery_status (message_id, recipient_uuid, state, updated_at)
-             VALUES (?1, ?2, ?3, ?4)",
+             VALUES (?1, ?2, ?3, ?4)"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![
                 status.message_id,
                 status.recipient_uuid,
@@ -594,29 +833,80 @@ impl StorageRepository for SqliteStorage {
         Ok(())
     }
 
-    fn get_delivery_statuses(&self, message_id: &str) -> Result<Vec<DeliveryStatus>, StorageError> {
+    fn get_delivery_statuses_for_messages(
+        &self,
+        message_ids: &[String],
+    ) -> Result<HashMap<String, Vec<DeliveryStatus>>, StorageError> {
+        let mut grouped: HashMap<String, Vec<DeliveryStatus>> = HashMap::new();
+        let conn = self.conn.lock().unwrap();
+        for chunk in message_ids.chunks(MAX_IN_PARAMS) {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT message_id, recipient_uuid, state, updated_at FROM delivery_status WHERE message_id IN ({})",
+                    Self::placeholders(chunk.len())
+                ))
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+
+            let rows = stmt
+                .query_map(params_from_iter(chunk), |row| {
+                    let state_str: String = row.get(2)?;
+                    let state = match state_str.as_str() {
+                        "sending" => DeliveryState::Sending,
+                        "sent" => DeliveryState::Sent,
+                        "delivered" => DeliveryState::Delivered,
+                        "read" => DeliveryState::Read,
+                        "failed" => DeliveryState::Failed,
+                        _ => DeliveryState::Sending,
+                    };
+
+                    Ok(DeliveryStatus {
+                        message_id: row.get(0)?,
+                        recipient_uuid: row.get(1)?,
+                        state,
+                        updated_at: row.get(3)?,
+                    })
+                })
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+
+            for row in rows {
+                let status = row.map_err(|e| StorageError::Database(e.to_string()))?;
+                grouped.entry(status.message_id.clone()).or_default().push(status);
+            }
+        }
+        Ok(grouped)
+    }
+
+    fn save_group_members(&self, group_id: &str, members: &[GroupMember]) -> Result<(), StorageError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute("DELETE FROM group_members WHERE group_id = ?1", params![group_id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        for member in members {
+            tx.execute(
+                "INSERT OR REPLACE INTO group_members (group_id, member_uuid, member_name, role)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![group_id, member.member_uuid, member.member_name, member.role],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMember>, StorageError> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT message_id, recipient_uuid, state, updated_at FROM delivery_status WHERE message_id = ?1"
+            "SELECT group_id, member_uuid, member_name, role FROM group_members WHERE group_id = ?1"
         ).map_err(|e| StorageError::Database(e.to_string()))?;
 
         let rows = stmt
-            .query_map(params![message_id], |row| {
-                let state_str: String = row.get(2)?;
-                let state = match state_str.as_str() {
-                    "sending" => DeliveryState::Sending,
-                    "sent" => DeliveryState::Sent,
-                    "delivered" => DeliveryState::Delivered,
-                    "read" => DeliveryState::Read,
-                    "failed" => DeliveryState::Failed,
-                    _ => DeliveryState::Sending,
-                };
-
-                Ok(DeliveryStatus {
-                    message_id: row.get(0)?,
-                    recipient_uuid: row.get(1)?,
-                    state,
-                    updated_at: row.get(3)?,
+            .query_map(params![group_id], |row| {
+                Ok(GroupMember {
+                    group_id: row.get(0)?,
+                    member_uuid: row.get(1)?,
+                    member_name: row.get(2)?,
+                    role: row.get(3)?,
                 })
             })
             .map_err(|e| StorageError::Database(e.to_string()))?;
@@ -631,18 +921,209 @@ impl StorageRepository for SqliteStorage {
         up_to_timestamp: i64,
     ) -> Result<(), StorageError> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE messages SET is_read = 1 WHERE conversation_id = ?1 AND timestamp <= ?2 AND is_read = 0",
+        let mut stmt = conn.prepare_cached(
+            "UPDATE messages SET is_read = 1 WHERE conversation_id = ?1 AND timestamp <= ?2 AND is_read = 0"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
             params![conversation_id, up_to_timestamp],
         ).map_err(|e| StorageError::Database(e.to_string()))?;
 
-        conn.execute(
+        let mut stmt = conn.prepare_cached(
             "UPDATE conversations SET unread_count = (
                 SELECT COUNT(*) FROM messages WHERE conversation_id = ?1 AND is_read = 0 AND is_outgoing = 0
+             ) WHERE id = ?1"
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        stmt.execute(
+            params![conversation_id],
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn mark_conversation_unread(&self, conversation_id: &str) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE messages SET is_read = 0 WHERE id = (
+                SELECT id FROM messages WHERE conversation_id = ?1 AND is_outgoing = 0
+                ORDER BY timestamp DESC LIMIT 1
+             )",
+            params![conversation_id],
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+
+        conn.execute(
+            "UPDATE conversations SET unread_count = MAX(1, (
+                SELECT COUNT(*) FROM messages WHERE conversation_id = ?1 AND is_read = 0 AND is_outgoing = 0
+             )), marked_unread_timestamp = (
+                SELECT MAX(timestamp) FROM messages WHERE conversation_id = ?1 AND is_outgoing = 0
              ) WHERE id = ?1",
             params![conversation_id],
         ).map_err(|e| StorageError::Database(e.to_string()))?;
 
         Ok(())
     }
+
+    fn get_setting(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        ).optional().map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        ).map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn clear_all(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(
+            "DELETE FROM reactions;
+             DELETE FROM delivery_status;
+             DELETE FROM group_members;
+             DELETE FROM messages;
+             DELETE FROM conversations;",
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn vacuum(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(conversation_id: &str, id: &str, timestamp: i64, received_at: i64) -> Message {
+        Message {
+            id: id.to_string(),
+            conversation_id: conversation_id.to_string(),
+            sender_uuid: "sender".to_string(),
+            sender_name: None,
+            timestamp,
+            server_timestamp: None,
+            received_at,
+            content: MessageContent::Text { body: id.to_string() },
+            quote: None,
+            is_outgoing: false,
+            is_read: true,
+            is_deleted: false,
+            is_edited: false,
+            link_preview: None,
+        }
+    }
+
+    #[test]
+    fn pagination_does_not_skip_messages_sharing_a_timestamp_across_pages() {
+        let storage = SqliteStorage::open(":memory:", None).unwrap();
+        let conv = storage.get_or_create_direct_conversation("uuid", None, None).unwrap();
+        for i in 0..5 {
+            storage.save_message(&message(&conv.id, &format!("early-{i}"), 1_000, 1_000)).unwrap();
+        }
+        for i in 0..5 {
+            storage.save_message(&message(&conv.id, &format!("same-{i}"), 2_000, 2_000 + i % 2)).unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = storage.list_messages(&conv.id, 3, cursor.as_ref()).unwrap();
+            let Some(oldest) = page.first() else { break };
+            cursor = Some(oldest.cursor());
+            seen.splice(0..0, page.into_iter().map(|m| m.id));
+        }
+
+        let mut expected = storage.list_messages(&conv.id, u32::MAX, None).unwrap();
+        expected.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        let expected: Vec<String> = expected.into_iter().map(|m| m.id).collect();
+        assert_eq!(seen.len(), 10);
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn batched_lookups_group_results_across_more_ids_than_one_statement_allows() {
+        let storage = SqliteStorage::open(":memory:", None).unwrap();
+        let conv = storage.get_or_create_direct_conversation("uuid", None, None).unwrap();
+        let count = MAX_IN_PARAMS * 2 + 7;
+        let ids: Vec<String> = (0..count).map(|i| format!("m{i}")).collect();
+        storage.begin_batch().unwrap();
+        for (i, id) in ids.iter().enumerate() {
+            storage.save_message(&message(&conv.id, id, i as i64, i as i64)).unwrap();
+            if i % 2 == 0 {
+                storage
+                    .save_reaction(&Reaction {
+                        id: format!("r{i}"),
+                        message_id: id.clone(),
+                        sender_uuid: "sender".to_string(),
+                        emoji: "👍".to_string(),
+                        timestamp: i as i64,
+                    })
+                    .unwrap();
+            }
+            storage
+                .save_delivery_status(&DeliveryStatus {
+                    message_id: id.clone(),
+                    recipient_uuid: "recipient".to_string(),
+                    state: DeliveryState::Delivered,
+                    updated_at: i as i64,
+                })
+                .unwrap();
+        }
+        storage.commit_batch().unwrap();
+
+        let reactions = storage.get_reactions_for_messages(&ids).unwrap();
+        assert_eq!(reactions.len(), count.div_ceil(2));
+        assert!(reactions.values().all(|r| r.len() == 1));
+        assert_eq!(reactions["m1000"][0].id, "r1000");
+        assert!(!reactions.contains_key("m1001"));
+
+        let statuses = storage.get_delivery_statuses_for_messages(&ids).unwrap();
+        assert_eq!(statuses.len(), count);
+        assert!(statuses.values().flatten().all(|s| s.state == DeliveryState::Delivered));
+    }
+
+    #[test]
+    #[ignore = "benchmark: cargo test --release bench_ -- --ignored --nocapture"]
+    fn bench_cached_statements_on_hot_paths() {
+        const MESSAGES: usize = 20_000;
+        const PAGES: usize = 2_000;
+
+        let run = |cached: bool| {
+            let storage = SqliteStorage::open(":memory:", None).unwrap();
+            let conv = storage.get_or_create_direct_conversation("uuid", None, None).unwrap();
+            let flush = || {
+                if !cached {
+                    storage.conn.lock().unwrap().flush_prepared_statement_cache();
+                }
+            };
+
+            let start = std::time::Instant::now();
+            for i in 0..MESSAGES {
+                storage.save_message(&message(&conv.id, &format!("m{i}"), i as i64, i as i64)).unwrap();
+                flush();
+            }
+            let inserts = start.elapsed();
+
+            let start = std::time::Instant::now();
+            for _ in 0..PAGES {
+                storage.list_messages(&conv.id, 100, None).unwrap();
+                flush();
+            }
+            (inserts, start.elapsed())
+        };
+
+        let (cached_inserts, cached_pages) = run(true);
+        let (uncached_inserts, uncached_pages) = run(false);
+        println!("save_message x{MESSAGES}: cached {cached_inserts:?}, re-prepared {uncached_inserts:?}");
+        println!("list_messages x{PAGES}: cached {cached_pages:?}, re-prepared {uncached_pages:?}");
+    }
 }
@@ -1,15 +1,16 @@
-use crate::app::{App, ConversationView, Focus};
+use crate::app::{App, ConnectionHealth, ConversationView, Focus, ListDensity};
 use crate::avatar::AvatarManager;
-use crate::storage::{ConversationType, MessageContent};
+use crate::image_cache::{ImageCache, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use crate::storage::{ConversationType, Message, MessageContent};
+use crate::util::{mask_spoilers, split_into_lines, truncate_to_width};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
-use ratatui_image::StatefulImage;
+use ratatui_image::{Image, StatefulImage};
 use ratatui_image::protocol::StatefulProtocol;
 
-const ITEM_HEIGHT: u16 = 4;
 const AVATAR_WIDTH: u16 = 8;
 
 pub fn render(
@@ -18,17 +19,32 @@ pub fn render(
     app: &App,
     focused: bool,
     avatar_manager: &mut Option<AvatarManager>,
+    image_cache: &mut Option<ImageCache>,
 ) -> (Rect, usize) {
     let in_filter_mode = app.focus == Focus::ConversationFilter;
     let has_filter = !app.filter_input.text.is_empty();
-    let border_color = if focused || in_filter_mode {
+    let border_color = if focused {
         Color::Cyan
     } else {
         Color::DarkGray
     };
 
+    let health_color = match app.connection_health {
+        ConnectionHealth::Healthy => Color::Green,
+        ConnectionHealth::Degraded => Color::Yellow,
+        ConnectionHealth::Unhealthy => Color::Red,
+    };
+
+    let total_unread = app.total_unread();
+    let title = if total_unread > 0 {
+        format!(" Conversations ({}) ", total_unread)
+    } else {
+        " Conversations ".to_string()
+    };
+
     let block = Block::default()
-        .title(" Conversations ")
+        .title(title)
+        .title(Line::from(Span::styled(" ● ", Style::default().fg(health_color))).right_aligned())
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
@@ -88,30 +104,79 @@ pub fn render(
             };
 
             let style = if i == app.selected {
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD)
+                Style::default().fg(Color::Yellow)
+            } else if conv.is_muted {
+                Style::default().fg(Color::Gray)
+            } else {
+                Style::default().fg(app.conversation_color(conv).unwrap_or(Color::White))
+            };
+            let style = if conv.is_muted {
+                style
+            } else {
+                style.add_modifier(Modifier::BOLD)
+            };
+
+            let unread_indicator = if conv_view.has_unread_emphasis() { " ●" } else { "" };
+            let status_marker = match (conv.is_archived, conv.is_muted) {
+                (true, _) => " 🗄",
+                (false, true) => " 🔇",
+                (false, false) => "",
+            };
+            let unregistered_marker = match &conv.recipient_number {
+                Some(number) if app.is_registered(number) == Some(false) => " ⊘",
+                _ => "",
+            };
+            let broadcast_marker = if app.broadcast_targets.contains(&conv.id) {
+                "✓ "
+            } else {
+                ""
+            };
+
+            let draft = app.draft_for(&conv.id);
+            let preview_style = if draft.is_some() && app.typing_label(&conv.id).is_none() {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let mut header = vec![
+                Span::styled(prefix, Style::default().fg(Color::DarkGray)),
+                Span::styled(broadcast_marker, Style::default().fg(Color::Magenta)),
+                Span::styled(name, style),
+                Span::styled(unread_indicator, Style::default().fg(Color::Green)),
+                Span::styled(status_marker, Style::default().fg(Color::DarkGray)),
+                Span::styled(unregistered_marker, Style::default().fg(Color::Red)),
+            ];
+
+            if app.list_density == ListDensity::Compact {
+                let header_width: usize = header.iter().map(|s| s.width()).sum();
+                let remaining = preview_width.saturating_sub(header_width + 1);
+                let (line1, line2) = match app.typing_label(&conv.id) {
+                    Some(label) => (label, String::new()),
+                    None => format_message_preview(conv_view, draft, remaining, app.preview_length),
+                };
+                let preview = truncate_to_width(&format!("{}{}", line1, line2), remaining);
+                header.push(Span::styled(format!("  {}", preview), preview_style));
+                return fixed_height_item(vec![Line::from(header)], app.list_density.item_height());
+            }
+
+            let preview_width = if image_cache.is_some() && preview_image_path(conv_view).is_some() {
+                preview_width.saturating_sub(THUMBNAIL_WIDTH as usize + 1)
             } else {
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
+                preview_width
+            };
+            let (line1, line2) = match app.typing_label(&conv.id) {
+                Some(label) => (truncate_to_width(&label, preview_width), String::new()),
+                None => format_message_preview(conv_view, draft, preview_width, app.preview_length),
             };
 
-            let unread_indicator = if has_unread(conv_view) { " ●" } else { "" };
-
-            let (line1, line2) = format_message_preview(conv_view, preview_width);
-            let preview_style = Style::default().fg(Color::Gray);
-
-            ListItem::new(vec![
-                Line::from(vec![
-                    Span::styled(prefix, Style::default().fg(Color::DarkGray)),
-                    Span::styled(name, style),
-                    Span::styled(unread_indicator, Style::default().fg(Color::Green)),
-                ]),
-                Line::from(Span::styled(format!(" {}", line1), preview_style)),
-                Line::from(Span::styled(format!(" {}", line2), preview_style)),
-                Line::default(),
-            ])
+            fixed_height_item(
+                vec![
+                    Line::from(header),
+                    Line::from(Span::styled(format!(" {}", line1), preview_style)),
+                    Line::from(Span::styled(format!(" {}", line2), preview_style)),
+                ],
+                app.list_density.item_height(),
+            )
         })
         .collect();
 
@@ -139,6 +204,12 @@ pub fn render(
         );
     }
 
+    if let Some(cache) = image_cache
+        && app.list_density == ListDensity::Comfortable
+    {
+        render_thumbnails(frame, list_area, app, cache, &filtered_indices, scroll_offset);
+    }
+
     if let Some(filter_area) = filter_area {
         render_filter_input(frame, filter_area, app);
     }
@@ -146,6 +217,11 @@ pub fn render(
     (list_inner, scroll_offset)
 }
 
+fn fixed_height_item(mut lines: Vec<Line<'static>>, height: u16) -> ListItem<'static> {
+    lines.resize(height as usize, Line::default());
+    ListItem::new(lines)
+}
+
 fn render_placeholder(frame: &mut Frame, area: Rect, name: &str, conv_type: ConversationType) {
     use ratatui::widgets::{Block, Paragraph};
 
@@ -178,30 +254,46 @@ fn render_placeholder(frame: &mut Frame, area: Rect, name: &str, conv_type: Conv
     frame.render_widget(placeholder, text_area);
 }
 
-fn has_unread(conv_view: &ConversationView) -> bool {
-    if let Some(ref messages) = conv_view.messages {
-        messages.iter().any(|m| !m.is_read && !m.is_outgoing)
-    } else {
-        false
+fn last_message(conv_view: &ConversationView) -> Option<&Message> {
+    conv_view
+        .last_message_preview
+        .as_ref()
+        .or_else(|| conv_view.messages.as_ref().and_then(|m| m.last()))
+}
+
+fn preview_image_path(conv_view: &ConversationView) -> Option<&str> {
+    match &last_message(conv_view)?.content {
+        MessageContent::Attachment { attachments } => attachments
+            .iter()
+            .find(|att| !att.view_once && ImageCache::is_image(att.content_type.as_deref()))
+            .and_then(|att| att.local_path.as_deref()),
+        _ => None,
     }
 }
 
-fn format_message_preview(conv_view: &ConversationView, max_width: usize) -> (String, String) {
-    let last_msg = conv_view
-        .last_message_preview
-        .as_ref()
-        .or_else(|| conv_view.messages.as_ref().and_then(|m| m.last()));
+fn format_message_preview(
+    conv_view: &ConversationView,
+    draft: Option<&str>,
+    max_width: usize,
+    max_length: usize,
+) -> (String, String) {
+    if let Some(draft) = draft {
+        let full = format!("Draft: {}", draft.replace('\n', " "));
+        return split_into_lines(&truncate_to_width(&full, max_length), max_width);
+    }
 
-    let Some(last_msg) = last_msg else {
+    let Some(last_msg) = last_message(conv_view) else {
         return (String::new(), String::new());
     };
 
     let prefix = if last_msg.is_outgoing { "You: " } else { "" };
 
     let content = match &last_msg.content {
-        MessageContent::Text { body } => body.replace('\n', " "),
+        MessageContent::Text { body } => mask_spoilers(body).replace('\n', " "),
         MessageContent::Attachment { attachments } => {
-            if attachments.len() == 1 {
+            if attachments.iter().any(|att| att.view_once) {
+                "👁 View-once media".to_string()
+            } else if attachments.len() == 1 {
                 format!(
                     "📎 {}",
                     attachments[0].filename.as_deref().unwrap_or("Attachment")
@@ -215,27 +307,7 @@ fn format_message_preview(conv_view: &ConversationView, max_width: usize) -> (St
     };
 
     let full = format!("{}{}", prefix, content);
-    split_into_lines(&full, max_width)
-}
-
-fn split_into_lines(s: &str, max_width: usize) -> (String, String) {
-    let chars: Vec<char> = s.chars().collect();
-    if chars.len() <= max_width {
-        return (s.to_string(), String::new());
-    }
-
-    let line1: String = chars[..max_width].iter().collect();
-    let remaining: Vec<char> = chars[max_width..].iter().copied().collect();
-
-    let line2 = if remaining.len() <= max_width {
-        remaining.iter().collect()
-    } else if max_width > 1 {
-        remaining[..max_width - 1].iter().collect::<String>() + "…"
-    } else {
-        String::new()
-    };
-
-    (line1, line2)
+    split_into_lines(&truncate_to_width(&full, max_length), max_width)
 }
 
 fn render_avatars_filtered(
@@ -246,7 +318,8 @@ fn render_avatars_filtered(
     filtered_indices: &[usize],
     scroll_offset: usize,
 ) {
-    let visible_count = (area.height / ITEM_HEIGHT) as usize;
+    let item_height = app.list_density.item_height();
+    let visible_count = (area.height / item_height) as usize;
 
     for (i, &conv_idx) in filtered_indices
         .iter()
@@ -256,8 +329,8 @@ fn render_avatars_filtered(
     {
         let conv = &app.conversations[conv_idx].conversation;
 
-        let y = area.y + (i as u16) * ITEM_HEIGHT;
-        if y + ITEM_HEIGHT > area.y + area.height {
+        let y = area.y + (i as u16) * item_height;
+        if y + item_height > area.y + area.height {
             break;
         }
 
@@ -265,7 +338,7 @@ fn render_avatars_filtered(
             x: area.x,
             y,
             width: area.width,
-            height: ITEM_HEIGHT,
+            height: item_height,
         };
 
         if let Some(protocol) = mgr.get_conversation_avatar(
@@ -285,6 +358,51 @@ fn render_avatars_filtered(
     }
 }
 
+fn render_thumbnails(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    cache: &mut ImageCache,
+    filtered_indices: &[usize],
+    scroll_offset: usize,
+) {
+    if area.width <= THUMBNAIL_WIDTH + 1 {
+        return;
+    }
+    let item_height = app.list_density.item_height();
+    let visible_count = (area.height / item_height) as usize;
+
+    for (i, &conv_idx) in filtered_indices
+        .iter()
+        .skip(scroll_offset)
+        .take(visible_count)
+        .enumerate()
+    {
+        let conv_view = &app.conversations[conv_idx];
+        if app.typing_label(&conv_view.conversation.id).is_some() || app.draft_for(&conv_view.conversation.id).is_some() {
+            continue;
+        }
+        let Some(path) = preview_image_path(conv_view) else {
+            continue;
+        };
+
+        let y = area.y + (i as u16) * item_height + 1;
+        if y + THUMBNAIL_HEIGHT > area.y + area.height {
+            break;
+        }
+
+        if let Some(protocol) = cache.get_thumbnail(path) {
+            let thumbnail_rect = Rect {
+                x: area.x + area.width - THUMBNAIL_WIDTH - 1,
+                y,
+                width: THUMBNAIL_WIDTH,
+                height: THUMBNAIL_HEIGHT,
+            };
+            frame.render_widget(Image::new(protocol), thumbnail_rect);
+        }
+    }
+}
+
 fn render_filter_input(frame: &mut Frame, area: Rect, app: &App) {
     use ratatui::widgets::Paragraph;
 
@@ -326,3 +444,19 @@ fn render_filter_input(frame: &mut Frame, area: Rect, app: &App) {
     let paragraph = Paragraph::new(Line::from(spans));
     frame.render_widget(paragraph, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_match_the_avatar_stride_in_both_densities() {
+        for density in [ListDensity::Compact, ListDensity::Comfortable] {
+            let stride = density.item_height();
+            for line_count in 1..=5 {
+                let lines = vec![Line::from("line"); line_count];
+                assert_eq!(fixed_height_item(lines, stride).height(), stride as usize);
+            }
+        }
+    }
+}
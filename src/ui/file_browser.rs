@@ -1,4 +1,4 @@
-use crate::app::App;
+use crate::app::{App, FileBrowserPurpose};
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
@@ -21,7 +21,13 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     let fb = &app.file_browser;
 
     let path_display = fb.current_dir.to_string_lossy();
-    let title = format!(" {} ", path_display);
+    let title = match fb.purpose {
+        _ if fb.showing_bookmarks => " Bookmarks (B: back) ".to_string(),
+        FileBrowserPurpose::Attach => format!(" {} ", path_display),
+        FileBrowserPurpose::ExportAttachments => {
+            format!(" Save attachments to {} (s: save here) ", path_display)
+        }
+    };
 
     let block = Block::default()
         .title(title)
@@ -32,7 +38,11 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(block, area);
 
     if fb.entries.is_empty() {
-        let empty = Paragraph::new("(empty directory)")
+        let empty = Paragraph::new(if fb.showing_bookmarks {
+            "(no bookmarks, press b to bookmark a directory)"
+        } else {
+            "(empty directory)"
+        })
             .style(Style::default().fg(Color::DarkGray));
         frame.render_widget(empty, inner_area);
         return;
@@ -1,9 +1,12 @@
 use crate::app::App;
+use crate::util::truncate_to_width;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub fn render(frame: &mut Frame, area: Rect, app: &App, focused: bool) {
     let border_color = if focused {
@@ -13,10 +16,26 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, focused: bool) {
     };
 
     let has_attachments = !app.pending_attachments.is_empty();
-    let title = if has_attachments {
-        format!(" Message [{} file(s)] ", app.pending_attachments.len())
+    let reply = app.active_reply();
+    let label = if reply.is_some() {
+        "Reply".to_string()
+    } else if app.broadcast_targets.is_empty() {
+        "Message".to_string()
     } else {
-        " Message ".to_string()
+        format!("Broadcast to {} conversations", app.broadcast_targets.len())
+    };
+    let title = if let Some(recording) = &app.voice_recording {
+        let elapsed = recording.started.elapsed().as_secs();
+        format!(
+            " {} ● REC {}:{:02} (Ctrl+r to attach, Esc to discard) ",
+            label,
+            elapsed / 60,
+            elapsed % 60
+        )
+    } else if has_attachments {
+        format!(" {} [{} file(s)] ", label, app.pending_attachments.len())
+    } else {
+        format!(" {} ", label)
     };
 
     let block = Block::default()
@@ -27,11 +46,26 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, focused: bool) {
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
 
-    let [attachment_area, input_area] = if has_attachments {
-        Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(inner_area)
-    } else {
-        [Rect::default(), inner_area]
-    };
+    let [reply_area, attachment_area, input_area] = Layout::vertical([
+        Constraint::Length(reply.is_some() as u16),
+        Constraint::Length(has_attachments as u16),
+        Constraint::Min(1),
+    ])
+    .areas(inner_area);
+
+    if let Some(quote) = reply {
+        let text = quote.summary().unwrap_or_default().replace('\n', " ");
+        let banner = format!("↱ {}: {}", app.contact_name(&quote.author_uuid), text);
+        let width = reply_area.width.saturating_sub(14) as usize;
+        let reply_line = Paragraph::new(Line::from(vec![
+            Span::styled(
+                truncate_to_width(&banner, width),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(" (Esc to cancel)", Style::default().fg(Color::DarkGray)),
+        ]));
+        frame.render_widget(reply_line, reply_area);
+    }
 
     if has_attachments {
         let names: Vec<String> = app
@@ -48,37 +82,73 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, focused: bool) {
     }
 
     let text = &app.input.text;
-    let cursor = app.input.cursor;
+    let width = input_area.width as usize;
 
-    let (before, after) = text.split_at(cursor.min(text.len()));
+    if !focused && text.is_empty() {
+        let placeholder = Paragraph::new(Span::styled(
+            "Type a message...",
+            Style::default().fg(Color::DarkGray),
+        ));
+        frame.render_widget(placeholder, input_area);
+        return;
+    }
 
-    let line = if focused {
-        let cursor_char = after.chars().next().unwrap_or(' ');
-        let after_cursor = if after.is_empty() {
-            ""
-        } else {
-            &after[cursor_char.len_utf8()..]
-        };
+    let cursor = app.input.cursor.min(text.len());
+    let (rows, (cursor_row, cursor_col)) = layout_input(text, cursor, width);
+    let first_row = cursor_row.saturating_sub((input_area.height as usize).saturating_sub(1));
 
-        Line::from(vec![
-            Span::raw(before),
-            Span::styled(
-                cursor_char.to_string(),
-                Style::default().bg(Color::White).fg(Color::Black),
-            ),
-            Span::raw(after_cursor),
-        ])
-    } else {
-        if text.is_empty() {
-            Line::from(Span::styled(
-                "Type a message...",
-                Style::default().fg(Color::DarkGray),
-            ))
-        } else {
-            Line::from(text.as_str())
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .skip(first_row)
+        .map(|(i, row)| {
+            let row_text = &text[row.clone()];
+            if !focused || i != cursor_row {
+                return Line::from(row_text);
+            }
+            let split = cursor.clamp(row.start, row.end) - row.start;
+            let (before, after) = row_text.split_at(split);
+            let cursor_char = after.chars().next().filter(|_| cursor_col < width);
+            let after_cursor = cursor_char.map_or(after, |c| &after[c.len_utf8()..]);
+            Line::from(vec![
+                Span::raw(before),
+                Span::styled(
+                    cursor_char.unwrap_or(' ').to_string(),
+                    Style::default().bg(Color::White).fg(Color::Black),
+                ),
+                Span::raw(after_cursor),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), input_area);
+}
+
+pub fn layout_input(text: &str, cursor: usize, width: usize) -> (Vec<Range<usize>>, (usize, usize)) {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let mut start = offset;
+        let mut used = 0;
+        for (i, c) in line.char_indices() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width && used > 0 {
+                rows.push(start..offset + i);
+                start = offset + i;
+                used = 0;
+            }
+            used += w;
         }
-    };
+        rows.push(start..offset + line.len());
+        offset += line.len() + 1;
+    }
 
-    let paragraph = Paragraph::new(line).wrap(ratatui::widgets::Wrap { trim: false });
-    frame.render_widget(paragraph, input_area);
+    let row = rows.iter().rposition(|r| r.start <= cursor).unwrap_or(0);
+    let col = text[rows[row].start..cursor].width();
+    if col >= width {
+        rows.insert(row + 1, cursor..cursor);
+        return (rows, (row + 1, 0));
+    }
+    (rows, (row, col))
 }
@@ -0,0 +1,56 @@
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+pub fn render(frame: &mut Frame, area: Rect, uri: &str) {
+    let mut lines: Vec<Line> = match QrCode::new(uri) {
+        Ok(code) => code
+            .render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .quiet_zone(true)
+            .build()
+            .lines()
+            .map(|row| Line::from(row.to_string()))
+            .collect(),
+        Err(_) => vec![Line::from(Span::styled(
+            uri.to_string(),
+            Style::default().fg(Color::Cyan),
+        ))],
+    };
+
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        "Scan in Signal on your phone: Settings > Linked devices",
+        Style::default().fg(Color::Gray),
+    )));
+    lines.push(Line::from(Span::styled(
+        "q: quit",
+        Style::default().fg(Color::Gray),
+    )));
+
+    let content_width = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0);
+    let width = (content_width + 4).max(30).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Link this device ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).centered(),
+        popup,
+    );
+}
@@ -1,27 +1,36 @@
-use crate::app::App;
-use crate::image_cache::ImageCache;
-use crate::storage::MessageContent;
+use crate::app::{App, is_audio, spinner_frame};
+use crate::image_cache::{ImageCache, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use crate::storage::{ConversationType, LinkPreview, Message, MessageContent, Reaction};
+use crate::util::{TimestampFormat, format_duration, mask_spoiler, relative_unit, mask_spoilers, spoiler_segments, truncate_to_width};
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui_image::Image;
 
 const DEFAULT_IMAGE_HEIGHT: u16 = 8;
+const DELETED_STYLE: Style = Style::new()
+    .fg(Color::DarkGray)
+    .add_modifier(Modifier::CROSSED_OUT.union(Modifier::DIM));
+const EDITED_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::ITALIC);
 
 fn calculate_message_height(
     msg: &crate::storage::Message,
     image_cache: &Option<ImageCache>,
     width: u16,
-    sender_prefix_len: usize,
+    footer: &[Line<'static>],
+    preview_length: usize,
+    timestamp_format: &TimestampFormat,
+    preview_images: bool,
 ) -> u16 {
     match &msg.content {
         MessageContent::Attachment { attachments } => {
             let mut h = 0u16;
             for att in attachments {
                 h += 1;
-                if ImageCache::is_image(att.content_type.as_deref())
+                if !att.view_once
+                    && ImageCache::is_image(att.content_type.as_deref())
                     && let Some(local_path) = &att.local_path
                 {
                     if let Some(cache) = image_cache.as_ref() {
@@ -31,29 +40,230 @@ fn calculate_message_height(
                     }
                 }
             }
-            h.max(1)
+            h.max(1) + footer.len() as u16
         }
         _ => {
-            let text = match &msg.content {
-                MessageContent::Text { body } => body.as_str(),
-                MessageContent::Sticker { .. } => "[Sticker]",
-                MessageContent::RemoteDeleted => "[Message deleted]",
-                MessageContent::Attachment { .. } => "",
-            };
-            let total_len = sender_prefix_len + text.len();
-            (total_len as u16).div_ceil(width.max(1)).max(1)
+            let mut body = text_message_body(
+                msg,
+                BodyStyle::default(),
+                preview_length,
+                timestamp_format,
+                width,
+                preview_images,
+            );
+            body.lines.extend(footer.iter().cloned());
+            wrapped_height(body, width)
+        }
+    }
+}
+
+fn message_footer(
+    msg: &Message,
+    failed: bool,
+    sending: bool,
+    reactions: Option<&Vec<Reaction>>,
+    selection_style: Style,
+) -> Vec<Line<'static>> {
+    let mut footer = Vec::new();
+    if sending {
+        let label = match &msg.content {
+            MessageContent::Attachment { attachments } => {
+                format!("  {} uploading {} file(s)…", spinner_frame(), attachments.len())
+            }
+            _ => format!("  {} sending…", spinner_frame()),
+        };
+        footer.push(Line::from(Span::styled(
+            label,
+            Style::default().fg(Color::DarkGray).patch(selection_style),
+        )));
+    }
+    if failed {
+        footer.push(Line::from(Span::styled(
+            "  ⚠ failed to send — r to retry",
+            Style::default().fg(Color::Red).patch(selection_style),
+        )));
+    }
+    if let Some(reactions) = reactions.filter(|r| !r.is_empty()) {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for reaction in reactions {
+            match counts.iter_mut().find(|(emoji, _)| *emoji == reaction.emoji) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&reaction.emoji, 1)),
+            }
         }
+        let summary = counts
+            .iter()
+            .map(|(emoji, count)| {
+                if *count > 1 {
+                    format!("{} {}", emoji, count)
+                } else {
+                    emoji.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        footer.push(Line::from(Span::styled(
+            format!("  {}", summary),
+            Style::default().fg(Color::Gray).patch(selection_style),
+        )));
     }
+    footer
 }
 
-fn get_sender_prefix_len(msg: &crate::storage::Message) -> usize {
-    let timestamp_len = 8;
-    let sender = if msg.is_outgoing {
+fn wrapped_height(text: Text, width: u16) -> u16 {
+    Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .line_count(width.max(1))
+        .max(1) as u16
+}
+
+fn sender_label(msg: &crate::storage::Message) -> &str {
+    if msg.is_outgoing {
         "You"
     } else {
         msg.sender_name.as_deref().unwrap_or("Unknown")
+    }
+}
+
+fn sender_style(msg: &crate::storage::Message, sender_color: Option<Color>, selection_style: Style) -> Style {
+    let color = if msg.is_outgoing {
+        Color::Cyan
+    } else {
+        sender_color.unwrap_or(Color::Green)
     };
-    timestamp_len + sender.len() + 4
+    Style::default()
+        .fg(color)
+        .add_modifier(Modifier::BOLD)
+        .patch(selection_style)
+}
+
+#[derive(Clone, Copy, Default)]
+struct BodyStyle {
+    selection: Style,
+    sender_color: Option<Color>,
+    reveal_spoilers: bool,
+}
+
+fn text_message_body(
+    msg: &crate::storage::Message,
+    style: BodyStyle,
+    preview_length: usize,
+    timestamp_format: &TimestampFormat,
+    width: u16,
+    preview_images: bool,
+) -> Text<'static> {
+    let BodyStyle {
+        selection: selection_style,
+        sender_color,
+        reveal_spoilers,
+    } = style;
+    let text = match &msg.content {
+        MessageContent::Text { body } => body.clone(),
+        MessageContent::Sticker {
+            pack_id,
+            sticker_id,
+        } => {
+            format!("[Sticker: {}#{}]", pack_id, sticker_id)
+        }
+        MessageContent::RemoteDeleted => "[Message deleted]".to_string(),
+        MessageContent::Attachment { .. } => String::new(),
+    };
+
+    let edited_suffix = if msg.is_edited { " (edited)" } else { "" };
+
+    let mut lines = Vec::new();
+    if let Some(quote) = &msg.quote {
+        let quoted = quote
+            .summary()
+            .map(|text| mask_spoilers(&text))
+            .unwrap_or_else(|| "[quoted message]".to_string());
+        lines.push(Line::from(Span::styled(
+            format!("  ↱ {}", truncate_to_width(&quoted.replace('\n', " "), preview_length)),
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC)
+                .patch(selection_style),
+        )));
+    }
+
+    let mut spans = vec![
+        Span::styled(
+            format!("[{}] ", format_timestamp(msg.timestamp, timestamp_format)),
+            Style::default().fg(Color::DarkGray).patch(selection_style),
+        ),
+        Span::styled(
+            format!("{}: ", sender_label(msg)),
+            sender_style(msg, sender_color, selection_style),
+        ),
+    ];
+    let body_style = if matches!(msg.content, MessageContent::RemoteDeleted) {
+        DELETED_STYLE.patch(selection_style)
+    } else {
+        selection_style
+    };
+    for (segment, spoiler) in spoiler_segments(&text) {
+        let span = match (spoiler, reveal_spoilers) {
+            (false, _) => Span::styled(segment.to_string(), body_style),
+            (true, true) => Span::styled(
+                segment.to_string(),
+                Style::default().add_modifier(Modifier::UNDERLINED).patch(selection_style),
+            ),
+            (true, false) => Span::styled(
+                mask_spoiler(segment),
+                Style::default().fg(Color::DarkGray).patch(selection_style),
+            ),
+        };
+        spans.push(span);
+    }
+    spans.push(Span::styled(edited_suffix, EDITED_STYLE.patch(selection_style)));
+    lines.push(Line::from(spans));
+
+    if let Some(preview) = &msg.link_preview {
+        lines.extend(link_preview_card(
+            preview,
+            width,
+            preview_images && preview.image_path.is_some(),
+            selection_style,
+        ));
+    }
+
+    Text::from(lines)
+}
+
+const LINK_PREVIEW_HEIGHT: u16 = THUMBNAIL_HEIGHT;
+
+fn link_preview_card(
+    preview: &LinkPreview,
+    width: u16,
+    thumbnail: bool,
+    selection_style: Style,
+) -> Vec<Line<'static>> {
+    let indent = if thumbnail {
+        " ".repeat(THUMBNAIL_WIDTH as usize + 1)
+    } else {
+        String::new()
+    };
+    let available = (width as usize).saturating_sub(4 + indent.len());
+    let title = preview.title.as_deref().unwrap_or(&preview.url);
+    let border = Style::default().fg(Color::Blue).patch(selection_style);
+    vec![
+        Line::from(vec![
+            Span::styled("  ┃ ", border),
+            Span::raw(indent.clone()),
+            Span::styled(
+                truncate_to_width(&title.replace('\n', " "), available),
+                Style::default().add_modifier(Modifier::BOLD).patch(selection_style),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  ┃ ", border),
+            Span::raw(indent),
+            Span::styled(
+                truncate_to_width(preview.host(), available),
+                Style::default().fg(Color::DarkGray).patch(selection_style),
+            ),
+        ]),
+    ]
 }
 
 pub fn render(
@@ -63,27 +273,84 @@ pub fn render(
     focused: bool,
     image_cache: &mut Option<ImageCache>,
 ) {
+    let accent = app
+        .selected_conversation()
+        .and_then(|c| app.conversation_color(&c.conversation));
     let border_color = if focused {
-        Color::Cyan
+        accent.unwrap_or(Color::Cyan)
     } else {
         Color::DarkGray
     };
 
     let title = app
         .selected_conversation()
-        .map(|c| format!(" {} ", c.conversation.display_name()))
+        .map(|c| {
+            let name = match &c.conversation.recipient_number {
+                Some(number) if app.is_registered(number) == Some(false) => {
+                    format!("{} (not on Signal)", c.conversation.display_name())
+                }
+                _ => c.conversation.display_name(),
+            };
+            let name = match c
+                .conversation
+                .group_id
+                .as_ref()
+                .and_then(|id| app.group_member_counts.get(id))
+            {
+                Some(count) => format!("{} ({})", name, count),
+                None => name,
+            };
+            let name = match app.contact_about(&c.conversation) {
+                Some(about) => format!("{} · {}", name, about),
+                None => name,
+            };
+            match c.conversation.expiration_seconds {
+                0 => format!(" {} ", name),
+                secs => format!(" {} ⏱ {} ", name, format_duration(secs)),
+            }
+        })
         .unwrap_or_else(|| " Messages ".to_string());
 
-    let block = Block::default()
-        .title(title)
+    let title_style = accent.map_or_else(Style::default, |color| Style::default().fg(color));
+    let mut block = Block::default()
+        .title(Span::styled(title, title_style))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
-    let inner_area = block.inner(area);
+    if let Some(conv) = app.selected_conversation()
+        && app.untrusted_identity(&conv.conversation).is_some()
+    {
+        block = block.title(
+            Line::styled(
+                " ⚠ Safety number changed ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )
+            .right_aligned(),
+        );
+    }
+
+    if let Some(msg) = app
+        .selected_conversation()
+        .and_then(|c| c.single_selected_message())
+    {
+        block = block.title_bottom(Span::styled(
+            timestamp_detail(msg),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+
+    let mut inner_area = block.inner(area);
     app.messages_height = inner_area.height as usize;
     frame.render_widget(block, area);
 
-    let (messages, mut scroll_offset, selection_range, sel_cursor) = {
+    if let Some(max_width) = app.max_message_width
+        && inner_area.width > max_width
+    {
+        inner_area.x += (inner_area.width - max_width) / 2;
+        inner_area.width = max_width;
+    }
+
+    let (messages, mut scroll_offset, selection_range, sel_cursor, flash_id, failed_sends, sending, reactions, compensation, revealed_spoilers) = {
         let Some(conv_view) = app.selected_conversation() else {
             let empty = Paragraph::new("No conversation selected")
                 .style(Style::default().fg(Color::DarkGray));
@@ -106,17 +373,52 @@ pub fn render(
 
         let sel_range = conv_view.selection.as_ref().map(|s| s.range());
         let sel_cursor = conv_view.selection.as_ref().map(|s| s.cursor);
-        (msgs.clone(), conv_view.scroll_offset, sel_range, sel_cursor)
+        let flash_id = conv_view.flash.as_ref().map(|(id, _)| id.clone());
+        (
+            msgs.clone(),
+            conv_view.scroll_offset,
+            sel_range,
+            sel_cursor,
+            flash_id,
+            conv_view.failed_sends.clone(),
+            conv_view.sending.clone(),
+            conv_view.reactions.clone(),
+            conv_view.scroll_compensation.clone(),
+            conv_view.revealed_spoilers.clone(),
+        )
     };
 
     let visible_height = inner_area.height as usize;
+    let is_group = app
+        .selected_conversation()
+        .is_some_and(|c| c.conversation.conversation_type == ConversationType::Group);
+    let preview_length = app.preview_length;
+    let timestamp_format = app.timestamp_format.clone();
+    let preview_images = app.link_preview_images && image_cache.is_some();
     let max_img_width = inner_area.width.saturating_sub(4);
 
     let mut msg_heights: Vec<usize> = Vec::with_capacity(messages.len());
     let mut total_content_height = 0usize;
     for msg in messages.iter() {
-        let prefix_len = get_sender_prefix_len(msg);
-        let h = calculate_message_height(msg, image_cache, inner_area.width, prefix_len) as usize;
+        let footer = message_footer(
+            msg,
+            failed_sends.contains(&msg.id),
+            sending.contains(&msg.id),
+            reactions.get(&msg.id),
+            Style::default(),
+        );
+        let h = calculate_message_height(
+            msg,
+            image_cache,
+            inner_area.width,
+            &footer,
+            preview_length,
+            &timestamp_format,
+            preview_images,
+        ) as usize;
+        if compensation.contains(&msg.id) {
+            scroll_offset = scroll_offset.saturating_add(h);
+        }
         msg_heights.push(h);
         total_content_height += h;
     }
@@ -142,8 +444,18 @@ pub fn render(
     }
 
     scroll_offset = scroll_offset.min(max_scroll);
+    let mut new_below = 0;
     if let Some(conv) = app.selected_conversation_mut() {
         conv.scroll_offset = scroll_offset;
+        conv.scroll_compensation.clear();
+        if let Some(range) = &selection_range {
+            conv.revealed_spoilers
+                .extend(range.clone().filter_map(|idx| messages.get(idx)).map(|m| m.id.clone()));
+        }
+        if scroll_offset == 0 {
+            conv.new_below = 0;
+        }
+        new_below = conv.new_below;
     }
 
     let target_bottom = total_content_height.saturating_sub(scroll_offset);
@@ -153,10 +465,7 @@ pub fn render(
     let mut start_idx = 0;
     let mut skip_lines_at_start = 0usize;
 
-    for (i, msg) in messages.iter().enumerate() {
-        let prefix_len = get_sender_prefix_len(msg);
-        let msg_height =
-            calculate_message_height(msg, image_cache, inner_area.width, prefix_len) as usize;
+    for (i, &msg_height) in msg_heights.iter().enumerate() {
         if cumulative_height + msg_height > target_top {
             start_idx = i;
             skip_lines_at_start = target_top.saturating_sub(cumulative_height);
@@ -180,29 +489,29 @@ pub fn render(
         let is_selected = selection_range
             .as_ref()
             .is_some_and(|r| r.contains(&msg_idx));
-        let selection_style = if is_selected {
+        let selection_style = if flash_id.as_deref() == Some(msg.id.as_str()) {
+            Style::default().bg(Color::Yellow).fg(Color::Black)
+        } else if is_selected {
             Style::default().add_modifier(Modifier::REVERSED)
         } else {
             Style::default()
         };
 
-        let sender = if msg.is_outgoing {
-            "You"
-        } else {
-            msg.sender_name.as_deref().unwrap_or("Unknown")
-        };
-        let timestamp = format_timestamp(msg.timestamp);
-        let sender_style = if msg.is_outgoing {
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD)
-                .patch(selection_style)
+        let footer = message_footer(
+            msg,
+            failed_sends.contains(&msg.id),
+            sending.contains(&msg.id),
+            reactions.get(&msg.id),
+            selection_style,
+        );
+        let sender = sender_label(msg);
+        let timestamp = format_timestamp(msg.timestamp, &timestamp_format);
+        let sender_color = if is_group {
+            app.contact_color(&msg.sender_uuid)
         } else {
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD)
-                .patch(selection_style)
+            accent
         };
+        let sender_style = sender_style(msg, sender_color, selection_style);
 
         match &msg.content {
             MessageContent::Attachment { attachments } => {
@@ -211,12 +520,27 @@ pub fn render(
                         break;
                     }
 
-                    let is_image = ImageCache::is_image(attachment.content_type.as_deref());
+                    let is_image = !attachment.view_once
+                        && ImageCache::is_image(attachment.content_type.as_deref());
                     let name = attachment
                         .filename
                         .as_deref()
                         .or(attachment.id.as_deref())
                         .unwrap_or("file");
+                    let label = match (attachment.view_once, attachment.viewed) {
+                        (true, true) if app.view_once_copies.iter().any(|(id, _)| *id == msg.id) => {
+                            "👁 View-once media (open, Esc to dismiss)".to_string()
+                        }
+                        (true, true) => "👁 View-once media (viewed)".to_string(),
+                        (true, false) if attachment.local_path.is_some() => {
+                            "👁 View-once media (select and press Enter to view)".to_string()
+                        }
+                        (true, false) => "👁 View-once media".to_string(),
+                        (false, _) if is_audio(attachment.content_type.as_deref()) => {
+                            format!("▶ {} (p to play)", name)
+                        }
+                        (false, _) => format!("📎 {}", name),
+                    };
 
                     let header = Line::from(vec![
                         Span::styled(
@@ -225,7 +549,7 @@ pub fn render(
                         ),
                         Span::styled(format!("{}: ", sender), sender_style),
                         Span::styled(
-                            format!("📎 {}", name),
+                            label,
                             Style::default().fg(Color::Yellow).patch(selection_style),
                         ),
                     ]);
@@ -262,56 +586,64 @@ pub fn render(
                                     height: img_end - img_start,
                                 };
                                 frame.render_widget(Image::new(protocol), image_rect);
-                            } else if cache.is_loading(local_path) {
-                                let placeholder_rect = Rect {
-                                    x: inner_area.x + 2,
-                                    y: inner_area.y + img_start,
-                                    width: max_img_width,
-                                    height: 1,
+                            } else {
+                                let placeholder = if cache.is_loading(local_path) {
+                                    Some("⏳ Loading image...")
+                                } else if cache.is_downloading(local_path) {
+                                    Some("⬇ Downloading...")
+                                } else if cache.is_failed(local_path) {
+                                    Some("⚠ Image unavailable")
+                                } else {
+                                    None
                                 };
-                                frame.render_widget(
-                                    Paragraph::new("⏳ Loading image...")
-                                        .style(Style::default().fg(Color::DarkGray)),
-                                    placeholder_rect,
-                                );
+                                if let Some(placeholder) = placeholder {
+                                    let placeholder_rect = Rect {
+                                        x: inner_area.x + 2,
+                                        y: inner_area.y + img_start,
+                                        width: max_img_width,
+                                        height: 1,
+                                    };
+                                    frame.render_widget(
+                                        Paragraph::new(placeholder)
+                                            .style(Style::default().fg(Color::DarkGray)),
+                                        placeholder_rect,
+                                    );
+                                }
                             }
                         }
                         y_offset += img_height as i16;
                     }
                 }
+
+                for line in footer {
+                    if y_offset >= 0 && y_offset < inner_area.height as i16 {
+                        let footer_rect = Rect {
+                            x: inner_area.x,
+                            y: inner_area.y + y_offset as u16,
+                            width: inner_area.width,
+                            height: 1,
+                        };
+                        frame.render_widget(Paragraph::new(line), footer_rect);
+                    }
+                    y_offset += 1;
+                }
             }
             _ => {
-                let text = match &msg.content {
-                    MessageContent::Text { body } => body.clone(),
-                    MessageContent::Sticker {
-                        pack_id,
-                        sticker_id,
-                    } => {
-                        format!("[Sticker: {}#{}]", pack_id, sticker_id)
-                    }
-                    MessageContent::RemoteDeleted => "[Message deleted]".to_string(),
-                    MessageContent::Attachment { .. } => unreachable!(),
-                };
-
-                let edited_suffix = if msg.is_edited { " (edited)" } else { "" };
-
-                let prefix = format!("[{}] {}: ", timestamp, sender);
-                let prefix_len = prefix.len();
-                let msg_height =
-                    calculate_message_height(msg, image_cache, inner_area.width, prefix_len) as i16;
-
-                let line = Line::from(vec![
-                    Span::styled(
-                        format!("[{}] ", timestamp),
-                        Style::default().fg(Color::DarkGray).patch(selection_style),
-                    ),
-                    Span::styled(format!("{}: ", sender), sender_style),
-                    Span::styled(text, selection_style),
-                    Span::styled(
-                        edited_suffix,
-                        Style::default().fg(Color::DarkGray).patch(selection_style),
-                    ),
-                ]);
+                let msg_height = msg_heights[msg_idx] as i16;
+                let footer_height = footer.len() as i16;
+                let mut body = text_message_body(
+                    msg,
+                    BodyStyle {
+                        selection: selection_style,
+                        sender_color,
+                        reveal_spoilers: is_selected || revealed_spoilers.contains(&msg.id),
+                    },
+                    preview_length,
+                    &timestamp_format,
+                    inner_area.width,
+                    preview_images,
+                );
+                body.lines.extend(footer);
 
                 let render_start = y_offset.max(0) as u16;
                 let render_end = (y_offset + msg_height).min(inner_area.height as i16) as u16;
@@ -324,10 +656,31 @@ pub fn render(
                         height: render_end - render_start,
                     };
                     frame.render_widget(
-                        Paragraph::new(line).wrap(ratatui::widgets::Wrap { trim: false }),
+                        Paragraph::new(body)
+                            .wrap(Wrap { trim: false })
+                            .scroll(((-y_offset).max(0) as u16, 0)),
                         msg_rect,
                     );
                 }
+
+                if preview_images
+                    && let Some(path) = msg.link_preview.as_ref().and_then(|p| p.image_path.as_deref())
+                    && let Some(cache) = image_cache.as_mut()
+                {
+                    let card_y = y_offset + msg_height - footer_height - LINK_PREVIEW_HEIGHT as i16;
+                    if card_y >= 0
+                        && card_y + LINK_PREVIEW_HEIGHT as i16 <= inner_area.height as i16
+                        && let Some(protocol) = cache.get_thumbnail(path)
+                    {
+                        let thumb_rect = Rect {
+                            x: inner_area.x + 4,
+                            y: inner_area.y + card_y as u16,
+                            width: THUMBNAIL_WIDTH.min(inner_area.width.saturating_sub(4)),
+                            height: LINK_PREVIEW_HEIGHT,
+                        };
+                        frame.render_widget(Image::new(protocol), thumb_rect);
+                    }
+                }
                 y_offset += msg_height;
             }
         }
@@ -342,6 +695,26 @@ pub fn render(
         conv.visible_range = Some((start_idx, end_idx));
     }
 
+    if new_below > 0 && inner_area.height > 0 {
+        let hint = if new_below == 1 {
+            " ↓ 1 new message ".to_string()
+        } else {
+            format!(" ↓ {} new messages ", new_below)
+        };
+        let hint_area = Rect {
+            x: inner_area.x,
+            y: inner_area.y + inner_area.height - 1,
+            width: inner_area.width,
+            height: 1,
+        };
+        frame.render_widget(
+            Paragraph::new(hint)
+                .alignment(ratatui::layout::Alignment::Center)
+                .style(Style::default().fg(Color::Black).bg(Color::Cyan)),
+            hint_area,
+        );
+    }
+
     if total_content_height > visible_height {
         let scroll_pct = if max_scroll > 0 {
             100 - (scroll_offset as f64 / max_scroll as f64 * 100.0) as u16
@@ -362,17 +735,91 @@ pub fn render(
     }
 }
 
-fn format_timestamp(timestamp: i64) -> String {
+fn timestamp_detail(msg: &Message) -> String {
     use chrono::{Local, TimeZone};
 
-    if let Some(dt) = Local.timestamp_millis_opt(timestamp).single() {
-        let now = Local::now();
-        if dt.date_naive() == now.date_naive() {
-            dt.format("%H:%M").to_string()
-        } else {
-            dt.format("%m/%d %H:%M").to_string()
+    let full = |ts: i64| {
+        Local
+            .timestamp_millis_opt(ts)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+            .unwrap_or_else(|| ts.to_string())
+    };
+    let mut parts = vec![format!("sent {}", full(msg.timestamp))];
+    if let Some(server) = msg.server_timestamp {
+        parts.push(format!("server {}", full(server)));
+    }
+    parts.push(format!("received {}", full(msg.received_at)));
+    format!(" {} ", parts.join(" · "))
+}
+
+fn format_timestamp(timestamp: i64, format: &TimestampFormat) -> String {
+    use chrono::{Local, TimeZone};
+
+    let Some(dt) = Local.timestamp_millis_opt(timestamp).single() else {
+        return "??:??".to_string();
+    };
+    let now = Local::now();
+    match format {
+        TimestampFormat::Absolute { today, other_day } => {
+            if dt.date_naive() == now.date_naive() {
+                dt.format(today).to_string()
+            } else {
+                dt.format(other_day).to_string()
+            }
         }
-    } else {
-        "??:??".to_string()
+        TimestampFormat::Relative => {
+            let seconds = (now - dt).num_seconds().max(0) as u32;
+            if seconds < 60 {
+                "now".to_string()
+            } else {
+                format!("{} ago", format_duration(seconds - seconds % relative_unit(seconds)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::widgets::Widget;
+
+    fn rendered_rows(text: &str, width: u16) -> u16 {
+        let area = Rect::new(0, 0, width, 20);
+        let mut buf = Buffer::empty(area);
+        Paragraph::new(text.to_string())
+            .wrap(Wrap { trim: false })
+            .render(area, &mut buf);
+        (0..area.height)
+            .rfind(|&y| (0..width).any(|x| buf[(x, y)].symbol() != " "))
+            .map_or(0, |y| y + 1)
+    }
+
+    #[test]
+    fn long_words_are_broken_across_lines() {
+        let word = "a".repeat(25);
+        assert_eq!(wrapped_height(Text::from(word.as_str()), 10), 3);
+        assert_eq!(wrapped_height(Text::from(word.as_str()), 10), rendered_rows(&word, 10));
+    }
+
+    #[test]
+    fn wraps_on_whitespace_rather_than_at_the_column() {
+        let text = "aaa bbbbbbb ccc";
+        assert_eq!(wrapped_height(Text::from(text), 10), 3);
+        assert_eq!(wrapped_height(Text::from(text), 10), rendered_rows(text, 10));
+    }
+
+    #[test]
+    fn keeps_runs_of_spaces() {
+        let text = "a         b         c";
+        assert_eq!(wrapped_height(Text::from(text), 10), rendered_rows(text, 10));
+        assert_eq!(wrapped_height(Text::from(text), 30), 1);
+    }
+
+    #[test]
+    fn empty_text_takes_one_line() {
+        assert_eq!(wrapped_height(Text::from(""), 10), 1);
+        assert_eq!(wrapped_height(Text::from("abc"), 0), 3);
     }
 }
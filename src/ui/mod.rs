@@ -1,19 +1,27 @@
 mod conversations;
 mod file_browser;
 mod input;
+mod link;
 mod messages;
+mod prompt;
+mod reactions;
+mod roster;
+mod safety;
+mod settings;
 
 use crate::app::{App, Focus};
 use crate::avatar::AvatarManager;
 use crate::image_cache::ImageCache;
 use ratatui::layout::{Constraint, Layout};
 use ratatui::style::{Color, Style};
-use ratatui::text::Span;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 pub fn render(frame: &mut Frame, app: &mut App, avatar_manager: &mut Option<AvatarManager>, image_cache: &mut Option<ImageCache>) {
-    let has_status = app.status_message.is_some();
+    let silence_label = app.silence_label();
+    let sync_label = app.sync_stale_label();
+    let has_status = app.status_message.is_some() || silence_label.is_some() || sync_label.is_some();
     let [main_area, status_area] = Layout::vertical([
         Constraint::Min(3),
         Constraint::Length(if has_status { 1 } else { 0 }),
@@ -27,12 +35,11 @@ pub fn render(frame: &mut Frame, app: &mut App, avatar_manager: &mut Option<Avat
     .areas(main_area);
 
     let input_width = right.width.saturating_sub(2) as usize;
-    let input_lines = if input_width > 0 {
-        ((app.input.text.len() + input_width) / input_width).max(1)
-    } else {
-        1
-    };
-    let input_height = (input_lines as u16 + 2).min(right.height / 2);
+    let input_lines = input::layout_input(&app.input.text, app.input.cursor.min(app.input.text.len()), input_width)
+        .0
+        .len();
+    let banners = app.active_reply().is_some() as u16 + !app.pending_attachments.is_empty() as u16;
+    let input_height = (input_lines as u16 + banners + 2).min(right.height / 2);
 
     let [messages_area, input_area] = Layout::vertical([
         Constraint::Min(3),
@@ -44,7 +51,7 @@ pub fn render(frame: &mut Frame, app: &mut App, avatar_manager: &mut Option<Avat
     app.layout_areas.messages = messages_area;
     app.layout_areas.input = input_area;
 
-    let (conversations_list_rect, conversations_scroll_offset) = conversations::render(frame, left, app, app.focus == Focus::Conversations, avatar_manager);
+    let (conversations_list_rect, conversations_scroll_offset) = conversations::render(frame, left, app, matches!(app.focus, Focus::Conversations | Focus::ConversationFilter), avatar_manager, image_cache);
     app.layout_areas.conversations_list = conversations_list_rect;
     app.layout_areas.conversations_scroll_offset = conversations_scroll_offset;
 
@@ -55,8 +62,52 @@ pub fn render(frame: &mut Frame, app: &mut App, avatar_manager: &mut Option<Avat
     }
     input::render(frame, input_area, app, app.focus == Focus::Input || app.focus == Focus::FileBrowser);
 
+    if let Some(ref text_prompt) = app.text_prompt {
+        prompt::render_input(frame, main_area, &text_prompt.title, &text_prompt.input);
+    }
+
+    if let Some(ref groups) = app.reaction_details {
+        reactions::render(frame, messages_area, groups);
+    }
+
+    if let Some(ref roster) = app.group_roster {
+        roster::render(frame, messages_area, roster);
+    }
+
+    if let Some(ref view) = app.safety_number {
+        safety::render(frame, messages_area, view);
+    }
+
+    if app.settings_open {
+        settings::render(frame, messages_area, app);
+    }
+
+    if let Some(ref uri) = app.device_link {
+        link::render(frame, main_area, uri);
+    }
+
+    if let Some(ref prompt) = app.prompt {
+        prompt::render_confirm(frame, main_area, prompt);
+    }
+
     if let Some(ref msg) = app.status_message {
         let status = Paragraph::new(Span::styled(msg, Style::default().fg(Color::Yellow)));
         frame.render_widget(status, status_area);
     }
+
+    let mut indicators = Vec::new();
+    if let Some(label) = sync_label {
+        indicators.push(Span::styled(label, Style::default().fg(Color::DarkGray)));
+    }
+    if let Some(label) = silence_label {
+        if !indicators.is_empty() {
+            indicators.push(Span::raw("  "));
+        }
+        indicators.push(Span::styled(label, Style::default().fg(Color::Magenta)));
+    }
+    if !indicators.is_empty() {
+        let indicator = Paragraph::new(Line::from(indicators))
+            .alignment(ratatui::layout::Alignment::Right);
+        frame.render_widget(indicator, status_area);
+    }
 }
@@ -0,0 +1,75 @@
+use crate::app::{InputState, Prompt};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+pub fn render_input(frame: &mut Frame, area: Rect, title: &str, input: &InputState) {
+    let width = (area.width / 2).max(40).min(area.width);
+    let height = 3.min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let text = &input.text;
+    let cursor_char = text[input.cursor..].chars().next();
+    let after_cursor = cursor_char
+        .map(|c| &text[input.cursor + c.len_utf8()..])
+        .unwrap_or("");
+
+    let line = Line::from(vec![
+        Span::raw(&text[..input.cursor]),
+        Span::styled(
+            cursor_char.map(|c| c.to_string()).unwrap_or(" ".to_string()),
+            Style::default().bg(Color::White).fg(Color::Black),
+        ),
+        Span::raw(after_cursor),
+    ]);
+
+    let block = Block::default()
+        .title(format!(" {} ", title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(line).block(block), popup);
+}
+
+pub fn render_confirm(frame: &mut Frame, area: Rect, prompt: &Prompt) {
+    let width = (area.width / 2).max(30).min(area.width);
+    let text_width = width.saturating_sub(2).max(1) as usize;
+    let text_lines = prompt.message.chars().count().div_ceil(text_width).max(1) as u16;
+    let height = (text_lines + 4).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let lines = vec![
+        Line::from(prompt.message.as_str()),
+        Line::default(),
+        Line::from(vec![
+            Span::styled("y/Enter", Style::default().fg(Color::Green)),
+            Span::raw(" confirm  "),
+            Span::styled("n/Esc", Style::default().fg(Color::Red)),
+            Span::raw(" cancel"),
+        ]),
+    ];
+
+    let block = Block::default()
+        .title(" Confirm ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
@@ -0,0 +1,45 @@
+use crate::app::ReactionGroup;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+pub fn render(frame: &mut Frame, area: Rect, groups: &[ReactionGroup]) {
+    let lines: Vec<Line> = groups
+        .iter()
+        .map(|group| {
+            Line::from(vec![
+                Span::raw(format!("{} ", group.emoji)),
+                Span::styled(
+                    format!("{} ", group.senders.len()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    group.senders.join(", "),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ])
+        })
+        .collect();
+
+    let width = (area.width * 2 / 3).max(20).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Reactions ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
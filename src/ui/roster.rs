@@ -0,0 +1,78 @@
+use crate::app::{ADMIN_ROLE, GroupRoster};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+pub fn render(frame: &mut Frame, area: Rect, roster: &GroupRoster) {
+    let mut lines: Vec<Line> = roster
+        .members
+        .iter()
+        .map(|member| {
+            let is_admin = member.role.as_deref() == Some(ADMIN_ROLE);
+            Line::from(vec![
+                Span::styled(
+                    if is_admin { "⭐ " } else { "   " },
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    member
+                        .member_name
+                        .clone()
+                        .unwrap_or_else(|| member.member_uuid.clone()),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ])
+        })
+        .collect();
+
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Loading members...",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    lines.push(Line::default());
+    if let Some(link) = &roster.invite_link {
+        lines.push(Line::from(vec![
+            Span::styled("Invite link: ", Style::default().fg(Color::Gray)),
+            Span::styled(link.clone(), Style::default().fg(Color::Cyan)),
+        ]));
+        lines.push(Line::from(Span::styled(
+            "y: copy link",
+            Style::default().fg(Color::Gray),
+        )));
+    } else if roster.is_admin {
+        lines.push(Line::from(Span::styled(
+            "l: create invite link",
+            Style::default().fg(Color::Gray),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            "Only admins can change group details, members or invite links",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let width = (area.width * 2 / 3).max(20).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(format!(" Members ({}) ", roster.members.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
@@ -0,0 +1,69 @@
+use crate::app::SafetyNumberView;
+use base64::prelude::*;
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+pub fn render(frame: &mut Frame, area: Rect, view: &SafetyNumberView) {
+    let data = view
+        .scannable
+        .as_deref()
+        .and_then(|s| BASE64_STANDARD.decode(s).ok())
+        .unwrap_or_else(|| view.safety_number.clone().into_bytes());
+
+    let mut lines: Vec<Line> = match QrCode::new(&data) {
+        Ok(code) => code
+            .render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .quiet_zone(true)
+            .build()
+            .lines()
+            .map(|row| Line::from(row.to_string()))
+            .collect(),
+        Err(_) => vec![Line::from(Span::styled(
+            "Unable to render QR code",
+            Style::default().fg(Color::DarkGray),
+        ))],
+    };
+
+    lines.push(Line::default());
+    let digits: Vec<char> = view.safety_number.chars().filter(|c| !c.is_whitespace()).collect();
+    for row in digits.chunks(20) {
+        let groups: Vec<String> = row.chunks(5).map(|g| g.iter().collect()).collect();
+        lines.push(Line::from(Span::styled(
+            groups.join(" "),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        "y: copy safety number",
+        Style::default().fg(Color::Gray),
+    )));
+
+    let content_width = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0);
+    let width = (content_width + 4).max(30).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(format!(" Safety number: {} ", view.name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).centered(),
+        popup,
+    );
+}
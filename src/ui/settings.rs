@@ -0,0 +1,48 @@
+use crate::app::{App, ListDensity};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+fn toggle_line(key: &str, label: &str, enabled: bool) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{}: ", key), Style::default().fg(Color::Gray)),
+        Span::raw(format!("{:<24}", label)),
+        if enabled {
+            Span::styled("on", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+        } else {
+            Span::styled("off", Style::default().fg(Color::DarkGray))
+        },
+    ])
+}
+
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let lines = vec![
+        toggle_line("c", "Compact list", app.list_density == ListDensity::Compact),
+        toggle_line("H", "Show empty conversations", app.show_empty_conversations),
+        toggle_line("r", "Send read receipts", app.send_read_receipts),
+        Line::default(),
+        Line::from(vec![
+            Span::styled("U: ", Style::default().fg(Color::Gray)),
+            Span::styled("Unregister account...", Style::default().fg(Color::Red)),
+        ]),
+    ];
+
+    let width = 40.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Settings ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
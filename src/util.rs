@@ -0,0 +1,218 @@
+use unicode_width::UnicodeWidthChar;
+
+pub const DEFAULT_PREVIEW_LENGTH: usize = 200;
+pub const DEFAULT_TODAY_FORMAT: &str = "%H:%M";
+pub const DEFAULT_OTHER_DAY_FORMAT: &str = "%m/%d %H:%M";
+const ELLIPSIS: char = '…';
+
+pub fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+const DURATION_UNITS: &[(char, u32)] = &[('w', 604_800), ('d', 86_400), ('h', 3_600), ('m', 60), ('s', 1)];
+
+pub fn format_duration(seconds: u32) -> String {
+    DURATION_UNITS
+        .iter()
+        .find(|(_, unit)| seconds >= *unit && seconds.is_multiple_of(*unit))
+        .map(|(suffix, unit)| format!("{}{}", seconds / unit, suffix))
+        .unwrap_or_else(|| format!("{}s", seconds))
+}
+
+pub fn relative_unit(seconds: u32) -> u32 {
+    match seconds {
+        0..3_600 => 60,
+        3_600..86_400 => 3_600,
+        86_400..604_800 => 86_400,
+        _ => 604_800,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimestampFormat {
+    Absolute { today: String, other_day: String },
+    Relative,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self::Absolute {
+            today: DEFAULT_TODAY_FORMAT.to_string(),
+            other_day: DEFAULT_OTHER_DAY_FORMAT.to_string(),
+        }
+    }
+}
+
+pub fn is_valid_time_format(format: &str) -> bool {
+    use chrono::format::{Item, StrftimeItems};
+
+    !format.is_empty() && StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
+pub fn normalize_e164(input: &str) -> Option<String> {
+    let number: String = input
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')'))
+        .collect();
+    let digits = number.strip_prefix('+')?;
+    let valid = (7..=15).contains(&digits.len())
+        && !digits.starts_with('0')
+        && digits.chars().all(|c| c.is_ascii_digit());
+    valid.then_some(number)
+}
+
+pub fn parse_duration(input: &str) -> Option<u32> {
+    let input = input.trim();
+    if input.is_empty() || input == "off" {
+        return Some(0);
+    }
+    let (number, multiplier) = match input.chars().last()? {
+        c if c.is_ascii_digit() => (input, 1),
+        c => {
+            let (_, unit) = DURATION_UNITS.iter().find(|(suffix, _)| *suffix == c)?;
+            (&input[..input.len() - 1], *unit)
+        }
+    };
+    number.trim().parse::<u32>().ok()?.checked_mul(multiplier)
+}
+
+fn take_width(s: &str, max_width: usize) -> (&str, &str) {
+    let mut width = 0;
+    for (idx, c) in s.char_indices() {
+        width += c.width().unwrap_or(0);
+        if width > max_width {
+            return s.split_at(idx);
+        }
+    }
+    (s, "")
+}
+
+pub fn parse_date(input: &str) -> Option<i64> {
+    use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+
+    let input = input.trim();
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp_millis())
+}
+
+pub fn truncate_to_width(s: &str, max_width: usize) -> String {
+    let (_, rest) = take_width(s, max_width);
+    if rest.is_empty() {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let (head, _) = take_width(s, max_width - 1);
+    format!("{}{}", head, ELLIPSIS)
+}
+
+pub fn split_into_lines(s: &str, max_width: usize) -> (String, String) {
+    let (line1, remaining) = take_width(s, max_width);
+    (line1.to_string(), truncate_to_width(remaining, max_width))
+}
+
+const SPOILER_MARKER: &str = "||";
+const SPOILER_MASK: char = '▒';
+
+pub fn spoiler_segments(text: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(SPOILER_MARKER) {
+        let after = &rest[start + SPOILER_MARKER.len()..];
+        let Some(len) = after.find(SPOILER_MARKER) else {
+            break;
+        };
+        if start > 0 {
+            segments.push((&rest[..start], false));
+        }
+        if len > 0 {
+            segments.push((&after[..len], true));
+        }
+        rest = &after[len + SPOILER_MARKER.len()..];
+    }
+    if !rest.is_empty() {
+        segments.push((rest, false));
+    }
+    segments
+}
+
+pub fn mask_spoiler(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_whitespace() {
+                c.to_string()
+            } else {
+                SPOILER_MASK.to_string().repeat(c.width().unwrap_or(0))
+            }
+        })
+        .collect()
+}
+
+pub fn strip_spoilers(text: &str) -> String {
+    spoiler_segments(text).into_iter().map(|(s, _)| s).collect()
+}
+
+pub fn mask_spoilers(text: &str) -> String {
+    spoiler_segments(text)
+        .into_iter()
+        .map(|(s, spoiler)| if spoiler { mask_spoiler(s) } else { s.to_string() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn normalizes_formatted_numbers_to_e164() {
+        assert_eq!(normalize_e164("+1 (555) 010-0000").as_deref(), Some("+15550100000"));
+        assert_eq!(normalize_e164("+4915112345678").as_deref(), Some("+4915112345678"));
+    }
+
+    #[test]
+    fn rejects_malformed_numbers() {
+        for bad in ["15550100000", "+0555010000", "+123", "+1234567890123456", "+1555abc0000", "", "+"] {
+            assert_eq!(normalize_e164(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn truncates_wide_glyphs_by_display_width() {
+        let truncated = truncate_to_width("日本語のテキスト", 7);
+        assert_eq!(truncated, "日本語…");
+        assert!(truncated.width() <= 7);
+    }
+
+    #[test]
+    fn leaves_text_that_fits_untouched() {
+        assert_eq!(truncate_to_width("héllo", 5), "héllo");
+        assert_eq!(truncate_to_width("😀😀", 4), "😀😀");
+        assert_eq!(truncate_to_width("anything", 0), "");
+    }
+
+    #[test]
+    fn splits_emoji_heavy_previews_without_overflowing() {
+        let preview = "😀".repeat(12);
+        let (line1, line2) = split_into_lines(&preview, 9);
+        assert_eq!(line1, "😀".repeat(4));
+        assert_eq!(line2, format!("{}…", "😀".repeat(4)));
+        assert!(line1.width() <= 9 && line2.width() <= 9);
+    }
+
+    #[test]
+    fn never_splits_inside_a_multibyte_character() {
+        let (line1, line2) = split_into_lines("ééééé", 3);
+        assert_eq!(line1, "ééé");
+        assert_eq!(line2, "éé");
+    }
+}